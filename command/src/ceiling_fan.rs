@@ -0,0 +1,123 @@
+use crate::command::{replace_state, Command, CommandError};
+use std::{cell::RefCell, rc::Rc};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Speed {
+    #[default]
+    Off,
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Default)]
+pub struct CeilingFan(Speed);
+
+impl CeilingFan {
+    pub fn speed(&self) -> Speed {
+        self.0
+    }
+
+    pub fn for_int_mut() -> Rc<RefCell<CeilingFan>> {
+        Rc::new(RefCell::new(CeilingFan::default()))
+    }
+}
+
+/// Sets the fan to one speed; undo restores whatever speed it had before.
+#[derive(Clone)]
+pub struct CeilingFanSpeed {
+    fan: Rc<RefCell<CeilingFan>>,
+    speed: Speed,
+    previous: Option<Speed>,
+}
+
+impl CeilingFanSpeed {
+    pub fn new(fan: &Rc<RefCell<CeilingFan>>, speed: Speed) -> CeilingFanSpeed {
+        CeilingFanSpeed {
+            fan: Rc::clone(fan),
+            speed,
+            previous: None,
+        }
+    }
+}
+
+impl Command for CeilingFanSpeed {
+    fn execute(&mut self) -> Result<(), CommandError> {
+        self.previous = Some(replace_state(&mut self.fan.borrow_mut().0, self.speed)?);
+        Ok(())
+    }
+
+    /// Does nothing if the command was never executed.
+    fn undo(&mut self) -> Result<(), CommandError> {
+        match self.previous {
+            Some(previous) => replace_state(&mut self.fan.borrow_mut().0, previous).map(|_| ()),
+            None => Ok(()),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self.speed {
+            Speed::Off => "CeilingFanOff",
+            Speed::Low => "CeilingFanLow",
+            Speed::Medium => "CeilingFanMedium",
+            Speed::High => "CeilingFanHigh",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::remote_control::RemoteControl;
+
+    #[test]
+    fn execute() {
+        let fan = CeilingFan::for_int_mut();
+        let mut command = CeilingFanSpeed::new(&fan, Speed::Medium);
+        command.execute().unwrap();
+        assert_eq!(fan.borrow().speed(), Speed::Medium);
+        assert_eq!(command.execute(), Err(CommandError::AlreadyInState));
+    }
+
+    #[test]
+    fn undo_restores_previous_speed() {
+        let fan = CeilingFan::for_int_mut();
+        let mut control = RemoteControl::new(2);
+        control.set_command(
+            0,
+            Box::new(CeilingFanSpeed::new(&fan, Speed::High)),
+            Box::new(CeilingFanSpeed::new(&fan, Speed::Off)),
+        );
+        control.set_command(
+            1,
+            Box::new(CeilingFanSpeed::new(&fan, Speed::Medium)),
+            Box::new(CeilingFanSpeed::new(&fan, Speed::Low)),
+        );
+        control.on_button_was_pressed(0).unwrap();
+        control.on_button_was_pressed(1).unwrap();
+        control.on_button_was_pressed(0).unwrap();
+        control.undo_button_was_pressed().unwrap();
+        assert_eq!(fan.borrow().speed(), Speed::Medium);
+        control.undo_button_was_pressed().unwrap();
+        assert_eq!(fan.borrow().speed(), Speed::High);
+        control.undo_button_was_pressed().unwrap();
+        assert_eq!(fan.borrow().speed(), Speed::Off);
+    }
+
+    #[test]
+    fn undo_before_execute() {
+        let fan = CeilingFan::for_int_mut();
+        let mut command = CeilingFanSpeed::new(&fan, Speed::Low);
+        command.undo().unwrap();
+        assert_eq!(fan.borrow().speed(), Speed::Off);
+    }
+
+    #[test]
+    fn name() {
+        let fan = CeilingFan::for_int_mut();
+        assert_eq!(
+            CeilingFanSpeed::new(&fan, Speed::Low).name(),
+            "CeilingFanLow"
+        );
+    }
+}
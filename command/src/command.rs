@@ -1,7 +1,98 @@
 use std::{cell::RefCell, rc::Rc};
 
-pub trait Command {
-    fn execute(&mut self);
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandError {
+    /// The device did not respond; its state is unchanged.
+    Unreachable,
+    /// The device was already in the state the command asked for.
+    AlreadyInState,
+}
+
+pub trait Command: CommandClone {
+    fn execute(&mut self) -> Result<(), CommandError>;
+
+    /// Reverts the most recent `execute`.
+    fn undo(&mut self) -> Result<(), CommandError>;
+
+    /// Whether remotes keep this command in their undo history once executed.
+    fn records_history(&self) -> bool {
+        true
+    }
+
+    /// Short name shown by remotes, the type name without its path or generic
+    /// arguments by default.
+    fn name(&self) -> &'static str {
+        let name = std::any::type_name::<Self>();
+        let name = name.split('<').next().unwrap_or(name);
+        name.rsplit("::").next().unwrap_or(name)
+    }
+}
+
+/// Lets a remote keep a copy of each command it executed, along with whatever
+/// that command needs to undo it. Implemented for every `Clone` command.
+pub trait CommandClone {
+    fn clone_box(&self) -> Box<dyn Command>;
+}
+
+impl<T: Command + Clone + 'static> CommandClone for T {
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+/// Sets `state` to `value` for a command, returning the previous state so the
+/// command can undo it.
+pub(crate) fn replace_state<T: PartialEq>(state: &mut T, value: T) -> Result<T, CommandError> {
+    if *state == value {
+        Err(CommandError::AlreadyInState)
+    } else {
+        Ok(std::mem::replace(state, value))
+    }
+}
+
+/// Placeholder for an empty remote slot.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoCommand;
+
+impl Command for NoCommand {
+    fn execute(&mut self) -> Result<(), CommandError> {
+        Ok(())
+    }
+
+    fn undo(&mut self) -> Result<(), CommandError> {
+        Ok(())
+    }
+
+    /// Pressing an empty slot changes nothing, so it must not discard redo.
+    fn records_history(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod command_tests {
+    use super::*;
+
+    #[derive(Clone)]
+    struct Wrapper<C>(C);
+
+    impl<C: Command + Clone + 'static> Command for Wrapper<C> {
+        fn execute(&mut self) -> Result<(), CommandError> {
+            self.0.execute()
+        }
+
+        fn undo(&mut self) -> Result<(), CommandError> {
+            self.0.undo()
+        }
+    }
+
+    #[test]
+    fn name() {
+        let light = Light::for_int_mut();
+        assert_eq!(NoCommand.name(), "NoCommand");
+        assert_eq!(LightOn::new(&light).name(), "LightOn");
+        assert_eq!(Wrapper(LightOn::new(&light)).name(), "Wrapper");
+    }
 }
 
 #[derive(Default)]
@@ -19,6 +110,15 @@ impl Light {
     pub fn for_int_mut() -> Rc<RefCell<Light>> {
         Rc::new(RefCell::new(Light::default()))
     }
+
+    fn switch(&mut self, on: bool) -> Result<(), CommandError> {
+        if self.0 == on {
+            Err(CommandError::AlreadyInState)
+        } else {
+            self.0 = on;
+            Ok(())
+        }
+    }
 }
 
 #[cfg(test)]
@@ -46,17 +146,22 @@ mod light_tests {
     }
 }
 
+#[derive(Clone)]
 pub struct LightOn(Rc<RefCell<Light>>);
 
 impl LightOn {
     pub fn new(light: &Rc<RefCell<Light>>) -> LightOn {
-        LightOn(Rc::clone(&light))
+        LightOn(Rc::clone(light))
     }
 }
 
 impl Command for LightOn {
-    fn execute(&mut self) {
-        self.0.borrow_mut().0 = true;
+    fn execute(&mut self) -> Result<(), CommandError> {
+        self.0.borrow_mut().switch(true)
+    }
+
+    fn undo(&mut self) -> Result<(), CommandError> {
+        self.0.borrow_mut().switch(false)
     }
 }
 
@@ -69,7 +174,65 @@ mod light_on_tests {
         let light = Light::for_int_mut();
         let mut command = LightOn::new(&light);
         assert!(light.borrow().is_off());
-        command.execute();
+        command.execute().unwrap();
+        assert!(light.borrow().is_on());
+    }
+
+    #[test]
+    fn already_on() {
+        let light = Rc::new(RefCell::new(Light(true)));
+        let mut command = LightOn::new(&light);
+        assert_eq!(command.execute(), Err(CommandError::AlreadyInState));
+        assert!(light.borrow().is_on());
+    }
+
+    #[test]
+    fn undo() {
+        let light = Light::for_int_mut();
+        let mut command = LightOn::new(&light);
+        command.execute().unwrap();
+        command.undo().unwrap();
+        assert!(light.borrow().is_off());
+    }
+}
+
+#[derive(Clone)]
+pub struct LightOff(Rc<RefCell<Light>>);
+
+impl LightOff {
+    pub fn new(light: &Rc<RefCell<Light>>) -> LightOff {
+        LightOff(Rc::clone(light))
+    }
+}
+
+impl Command for LightOff {
+    fn execute(&mut self) -> Result<(), CommandError> {
+        self.0.borrow_mut().switch(false)
+    }
+
+    fn undo(&mut self) -> Result<(), CommandError> {
+        self.0.borrow_mut().switch(true)
+    }
+}
+
+#[cfg(test)]
+mod light_off_tests {
+    use super::*;
+
+    #[test]
+    fn execute() {
+        let light = Rc::new(RefCell::new(Light(true)));
+        let mut command = LightOff::new(&light);
+        command.execute().unwrap();
+        assert!(light.borrow().is_off());
+    }
+
+    #[test]
+    fn undo() {
+        let light = Rc::new(RefCell::new(Light(true)));
+        let mut command = LightOff::new(&light);
+        command.execute().unwrap();
+        command.undo().unwrap();
         assert!(light.borrow().is_on());
     }
 }
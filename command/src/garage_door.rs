@@ -0,0 +1,115 @@
+use crate::command::{replace_state, Command, CommandError};
+use std::{cell::RefCell, rc::Rc};
+
+/// Opening and closing complete at once; `Stopped` is a door halted part-way.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DoorState {
+    Open,
+    #[default]
+    Closed,
+    Stopped,
+}
+
+#[derive(Default)]
+pub struct GarageDoor(DoorState);
+
+impl GarageDoor {
+    pub fn state(&self) -> DoorState {
+        self.0
+    }
+
+    pub fn for_int_mut() -> Rc<RefCell<GarageDoor>> {
+        Rc::new(RefCell::new(GarageDoor::default()))
+    }
+}
+
+/// Opens, closes or stops the door; undo restores whatever state it had before.
+#[derive(Clone)]
+pub struct GarageDoorCommand {
+    door: Rc<RefCell<GarageDoor>>,
+    state: DoorState,
+    previous: Option<DoorState>,
+}
+
+impl GarageDoorCommand {
+    fn new(door: &Rc<RefCell<GarageDoor>>, state: DoorState) -> GarageDoorCommand {
+        GarageDoorCommand {
+            door: Rc::clone(door),
+            state,
+            previous: None,
+        }
+    }
+
+    pub fn open(door: &Rc<RefCell<GarageDoor>>) -> GarageDoorCommand {
+        GarageDoorCommand::new(door, DoorState::Open)
+    }
+
+    pub fn close(door: &Rc<RefCell<GarageDoor>>) -> GarageDoorCommand {
+        GarageDoorCommand::new(door, DoorState::Closed)
+    }
+
+    pub fn stop(door: &Rc<RefCell<GarageDoor>>) -> GarageDoorCommand {
+        GarageDoorCommand::new(door, DoorState::Stopped)
+    }
+}
+
+impl Command for GarageDoorCommand {
+    fn execute(&mut self) -> Result<(), CommandError> {
+        self.previous = Some(replace_state(&mut self.door.borrow_mut().0, self.state)?);
+        Ok(())
+    }
+
+    /// Does nothing if the command was never executed.
+    fn undo(&mut self) -> Result<(), CommandError> {
+        match self.previous {
+            Some(previous) => replace_state(&mut self.door.borrow_mut().0, previous).map(|_| ()),
+            None => Ok(()),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self.state {
+            DoorState::Open => "GarageDoorOpen",
+            DoorState::Closed => "GarageDoorClose",
+            DoorState::Stopped => "GarageDoorStop",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open_stop_close() {
+        let door = GarageDoor::for_int_mut();
+        GarageDoorCommand::open(&door).execute().unwrap();
+        assert_eq!(door.borrow().state(), DoorState::Open);
+        GarageDoorCommand::stop(&door).execute().unwrap();
+        assert_eq!(door.borrow().state(), DoorState::Stopped);
+        GarageDoorCommand::close(&door).execute().unwrap();
+        assert_eq!(door.borrow().state(), DoorState::Closed);
+    }
+
+    #[test]
+    fn already_closed() {
+        let door = GarageDoor::for_int_mut();
+        assert_eq!(
+            GarageDoorCommand::close(&door).execute(),
+            Err(CommandError::AlreadyInState)
+        );
+    }
+
+    #[test]
+    fn undo() {
+        let door = GarageDoor::for_int_mut();
+        let mut open = GarageDoorCommand::open(&door);
+        let mut stop = GarageDoorCommand::stop(&door);
+        open.execute().unwrap();
+        stop.execute().unwrap();
+        stop.undo().unwrap();
+        assert_eq!(door.borrow().state(), DoorState::Open);
+        open.undo().unwrap();
+        assert_eq!(door.borrow().state(), DoorState::Closed);
+    }
+}
@@ -0,0 +1,97 @@
+use crate::command::{Command, CommandError};
+
+/// Undo and redo stacks of executed commands. Recording a new command discards
+/// everything that could have been redone.
+#[derive(Default)]
+pub struct History {
+    undo: Vec<Box<dyn Command>>,
+    redo: Vec<Box<dyn Command>>,
+}
+
+impl History {
+    /// Records `command`, which has just been executed, unless it opts out
+    /// through `Command::records_history`.
+    pub fn record(&mut self, command: Box<dyn Command>) {
+        if command.records_history() {
+            self.undo.push(command);
+            self.redo.clear();
+        }
+    }
+
+    /// Undoes the most recently executed command; false if there is none. A
+    /// command that fails to undo stays on the undo stack.
+    pub fn undo(&mut self) -> Result<bool, CommandError> {
+        Self::replay(&mut self.undo, &mut self.redo, |command| command.undo())
+    }
+
+    /// Executes again the most recently undone command; false if there is none.
+    /// A command that fails stays on the redo stack.
+    pub fn redo(&mut self) -> Result<bool, CommandError> {
+        Self::replay(&mut self.redo, &mut self.undo, |command| command.execute())
+    }
+
+    fn replay(
+        from: &mut Vec<Box<dyn Command>>,
+        to: &mut Vec<Box<dyn Command>>,
+        run: impl FnOnce(&mut dyn Command) -> Result<(), CommandError>,
+    ) -> Result<bool, CommandError> {
+        let Some(mut command) = from.pop() else {
+            return Ok(false);
+        };
+        match run(command.as_mut()) {
+            Ok(()) => {
+                to.push(command);
+                Ok(true)
+            }
+            Err(error) => {
+                from.push(command);
+                Err(error)
+            }
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::{Light, LightOff, LightOn};
+
+    #[test]
+    fn undo_and_redo() {
+        let light = Light::for_int_mut();
+        let mut command = LightOn::new(&light);
+        let mut history = History::default();
+        assert!(!history.can_undo());
+        command.execute().unwrap();
+        history.record(Box::new(command));
+        assert!(history.can_undo());
+        assert_eq!(history.undo(), Ok(true));
+        assert!(light.borrow().is_off());
+        assert!(history.can_redo());
+        assert_eq!(history.redo(), Ok(true));
+        assert!(light.borrow().is_on());
+        assert!(!history.can_redo());
+        assert_eq!(history.redo(), Ok(false));
+    }
+
+    #[test]
+    fn failed_undo_stays_on_stack() {
+        let light = Light::for_int_mut();
+        let mut command = LightOn::new(&light);
+        let mut history = History::default();
+        command.execute().unwrap();
+        history.record(Box::new(command));
+        LightOff::new(&light).execute().unwrap();
+        assert_eq!(history.undo(), Err(CommandError::AlreadyInState));
+        assert!(history.can_undo());
+        assert!(!history.can_redo());
+    }
+}
@@ -1,3 +1,9 @@
+pub mod ceiling_fan;
 pub mod command;
+pub mod garage_door;
+pub mod history;
+pub mod macro_command;
+pub mod remote_control;
 pub mod simple_remote_control;
-
+pub mod stereo;
+pub mod thermostat;
@@ -0,0 +1,250 @@
+use crate::command::{Command, CommandError};
+
+/// Runs several commands as one, undoing them in reverse order.
+pub struct MacroCommand {
+    commands: Vec<Box<dyn Command>>,
+    /// Which steps changed their device in the last `execute`; steps whose
+    /// device was already in the requested state are not undone.
+    applied: Vec<bool>,
+}
+
+impl MacroCommand {
+    pub fn new(commands: Vec<Box<dyn Command>>) -> MacroCommand {
+        let applied = vec![false; commands.len()];
+        MacroCommand { commands, applied }
+    }
+
+    /// Undoes the applied steps before `end` in reverse order, best effort.
+    fn roll_back(&mut self, end: usize) {
+        for index in (0..end).rev() {
+            if std::mem::take(&mut self.applied[index]) {
+                let _ = self.commands[index].undo();
+            }
+        }
+    }
+}
+
+impl Clone for MacroCommand {
+    fn clone(&self) -> Self {
+        MacroCommand {
+            commands: self
+                .commands
+                .iter()
+                .map(|command| command.clone_box())
+                .collect(),
+            applied: self.applied.clone(),
+        }
+    }
+}
+
+impl Command for MacroCommand {
+    /// A step whose device is already in the requested state is skipped. If a
+    /// step's device is unreachable, the steps before it are undone and the
+    /// error returned, so no device is left half-way through the macro.
+    /// Rolling back is best effort: a step that also fails to undo is left as
+    /// it is. Fails with `AlreadyInState` if every step was skipped.
+    fn execute(&mut self) -> Result<(), CommandError> {
+        self.applied.fill(false);
+        for index in 0..self.commands.len() {
+            match self.commands[index].execute() {
+                Ok(()) => self.applied[index] = true,
+                Err(CommandError::AlreadyInState) => {}
+                Err(error) => {
+                    self.roll_back(index);
+                    return Err(error);
+                }
+            }
+        }
+        if self.applied.contains(&true) || self.commands.is_empty() {
+            Ok(())
+        } else {
+            Err(CommandError::AlreadyInState)
+        }
+    }
+
+    /// Undoes the applied steps in reverse order; a step whose device is
+    /// already back in its earlier state counts as undone. If a device is
+    /// unreachable, the steps already undone are executed again, as in
+    /// `execute`.
+    fn undo(&mut self) -> Result<(), CommandError> {
+        for index in (0..self.commands.len()).rev() {
+            if !self.applied[index] {
+                continue;
+            }
+            match self.commands[index].undo() {
+                Ok(()) | Err(CommandError::AlreadyInState) => {}
+                Err(error) => {
+                    for later in index + 1..self.commands.len() {
+                        if self.applied[later] {
+                            let _ = self.commands[later].execute();
+                        }
+                    }
+                    return Err(error);
+                }
+            }
+        }
+        self.applied.fill(false);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::{Light, LightOff, LightOn, NoCommand};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[derive(Clone)]
+    struct Recorder {
+        name: &'static str,
+        calls: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Command for Recorder {
+        fn execute(&mut self) -> Result<(), CommandError> {
+            self.calls
+                .borrow_mut()
+                .push(format!("execute {}", self.name));
+            Ok(())
+        }
+
+        fn undo(&mut self) -> Result<(), CommandError> {
+            self.calls.borrow_mut().push(format!("undo {}", self.name));
+            Ok(())
+        }
+    }
+
+    #[derive(Clone)]
+    struct Unreachable;
+
+    impl Command for Unreachable {
+        fn execute(&mut self) -> Result<(), CommandError> {
+            Err(CommandError::Unreachable)
+        }
+
+        fn undo(&mut self) -> Result<(), CommandError> {
+            Err(CommandError::Unreachable)
+        }
+    }
+
+    fn recorder(name: &'static str, calls: &Rc<RefCell<Vec<String>>>) -> Box<dyn Command> {
+        Box::new(Recorder {
+            name,
+            calls: Rc::clone(calls),
+        })
+    }
+
+    #[test]
+    fn execute_and_undo() {
+        let calls = Rc::default();
+        let mut command = MacroCommand::new(vec![recorder("a", &calls), recorder("b", &calls)]);
+        command.execute().unwrap();
+        command.undo().unwrap();
+        assert_eq!(
+            *calls.borrow(),
+            vec!["execute a", "execute b", "undo b", "undo a"]
+        );
+    }
+
+    #[test]
+    fn lights() {
+        let kitchen = Light::for_int_mut();
+        let porch = Light::for_int_mut();
+        let mut command = MacroCommand::new(vec![
+            Box::new(LightOn::new(&kitchen)),
+            Box::new(LightOn::new(&porch)),
+            Box::new(LightOff::new(&porch)),
+        ]);
+        command.execute().unwrap();
+        assert!(kitchen.borrow().is_on());
+        assert!(porch.borrow().is_off());
+        command.undo().unwrap();
+        assert!(kitchen.borrow().is_off());
+        assert!(porch.borrow().is_off());
+    }
+
+    #[test]
+    fn rolls_back_on_failure() {
+        let calls = Rc::default();
+        let light = Light::for_int_mut();
+        let mut command = MacroCommand::new(vec![
+            Box::new(LightOn::new(&light)),
+            recorder("a", &calls),
+            Box::new(Unreachable),
+            recorder("b", &calls),
+        ]);
+        assert_eq!(command.execute(), Err(CommandError::Unreachable));
+        assert!(light.borrow().is_off());
+        assert_eq!(*calls.borrow(), vec!["execute a", "undo a"]);
+    }
+
+    #[test]
+    fn skips_steps_already_in_state() {
+        let kitchen = Light::for_int_mut();
+        LightOn::new(&kitchen).execute().unwrap();
+        let porch = Light::for_int_mut();
+        let mut command = MacroCommand::new(vec![
+            Box::new(LightOn::new(&kitchen)),
+            Box::new(LightOn::new(&porch)),
+        ]);
+        command.execute().unwrap();
+        assert!(porch.borrow().is_on());
+        command.undo().unwrap();
+        assert!(kitchen.borrow().is_on());
+        assert!(porch.borrow().is_off());
+        assert_eq!(command.execute(), Ok(()));
+        assert_eq!(
+            MacroCommand::new(vec![Box::new(LightOn::new(&kitchen))]).execute(),
+            Err(CommandError::AlreadyInState)
+        );
+    }
+
+    #[test]
+    fn rollback_skips_steps_already_in_state() {
+        let calls = Rc::default();
+        let light = Light::for_int_mut();
+        LightOn::new(&light).execute().unwrap();
+        let mut command = MacroCommand::new(vec![
+            Box::new(LightOn::new(&light)),
+            recorder("a", &calls),
+            Box::new(Unreachable),
+        ]);
+        assert_eq!(command.execute(), Err(CommandError::Unreachable));
+        assert!(light.borrow().is_on());
+        assert_eq!(*calls.borrow(), vec!["execute a", "undo a"]);
+    }
+
+    #[test]
+    fn failed_execute_forgets_earlier_run() {
+        let calls = Rc::default();
+        let mut command = MacroCommand::new(vec![
+            recorder("a", &calls),
+            Box::new(NoCommand),
+            recorder("b", &calls),
+        ]);
+        command.execute().unwrap();
+        command.commands[1] = Box::new(Unreachable);
+        assert_eq!(command.execute(), Err(CommandError::Unreachable));
+        calls.borrow_mut().clear();
+        command.undo().unwrap();
+        assert!(calls.borrow().is_empty());
+    }
+
+    #[test]
+    fn restores_on_failed_undo() {
+        let calls = Rc::default();
+        let mut command = MacroCommand::new(vec![
+            recorder("a", &calls),
+            Box::new(NoCommand),
+            recorder("b", &calls),
+        ]);
+        command.execute().unwrap();
+        command.commands[1] = Box::new(Unreachable);
+        assert_eq!(command.undo(), Err(CommandError::Unreachable));
+        assert_eq!(
+            *calls.borrow(),
+            vec!["execute a", "execute b", "undo b", "execute b"]
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use crate::command::{Command, CommandError, NoCommand};
+use crate::history::History;
+use std::fmt;
+
+/// Remote with a fixed number of slots, each with an on and an off button.
+/// Slots start out empty, holding `NoCommand`.
+pub struct RemoteControl {
+    on_commands: Vec<Box<dyn Command>>,
+    off_commands: Vec<Box<dyn Command>>,
+    history: History,
+}
+
+impl RemoteControl {
+    pub fn new(slots: usize) -> RemoteControl {
+        RemoteControl {
+            on_commands: (0..slots).map(|_| no_command()).collect(),
+            off_commands: (0..slots).map(|_| no_command()).collect(),
+            history: History::default(),
+        }
+    }
+
+    pub fn slots(&self) -> usize {
+        self.on_commands.len()
+    }
+
+    /// Panics if `slot` is out of range.
+    pub fn set_command(&mut self, slot: usize, on: Box<dyn Command>, off: Box<dyn Command>) {
+        self.on_commands[slot] = on;
+        self.off_commands[slot] = off;
+    }
+
+    /// Panics if `slot` is out of range.
+    pub fn on_button_was_pressed(&mut self, slot: usize) -> Result<(), CommandError> {
+        let command = &mut self.on_commands[slot];
+        command.execute()?;
+        self.history.record(command.clone_box());
+        Ok(())
+    }
+
+    /// Panics if `slot` is out of range.
+    pub fn off_button_was_pressed(&mut self, slot: usize) -> Result<(), CommandError> {
+        let command = &mut self.off_commands[slot];
+        command.execute()?;
+        self.history.record(command.clone_box());
+        Ok(())
+    }
+
+    /// Undoes the most recent press; false if there is nothing to undo.
+    pub fn undo_button_was_pressed(&mut self) -> Result<bool, CommandError> {
+        self.history.undo()
+    }
+
+    /// Repeats the most recently undone press; false if there is nothing to redo.
+    pub fn redo_button_was_pressed(&mut self) -> Result<bool, CommandError> {
+        self.history.redo()
+    }
+}
+
+fn no_command() -> Box<dyn Command> {
+    Box::new(NoCommand)
+}
+
+impl fmt::Display for RemoteControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (slot, (on, off)) in self.on_commands.iter().zip(&self.off_commands).enumerate() {
+            writeln!(f, "[slot {}] {} / {}", slot, on.name(), off.name())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::{Light, LightOff, LightOn};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn light_remote() -> (RemoteControl, Rc<RefCell<Light>>) {
+        let light = Light::for_int_mut();
+        let mut control = RemoteControl::new(3);
+        control.set_command(
+            1,
+            Box::new(LightOn::new(&light)),
+            Box::new(LightOff::new(&light)),
+        );
+        (control, light)
+    }
+
+    #[test]
+    fn on_and_off() {
+        let (mut control, light) = light_remote();
+        control.on_button_was_pressed(1).unwrap();
+        assert!(light.borrow().is_on());
+        control.off_button_was_pressed(1).unwrap();
+        assert!(light.borrow().is_off());
+    }
+
+    #[test]
+    fn empty_slot() {
+        let (mut control, light) = light_remote();
+        control.on_button_was_pressed(0).unwrap();
+        control.off_button_was_pressed(2).unwrap();
+        assert!(light.borrow().is_off());
+        assert_eq!(control.slots(), 3);
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let (mut control, light) = light_remote();
+        control.on_button_was_pressed(1).unwrap();
+        control.off_button_was_pressed(1).unwrap();
+        assert_eq!(control.undo_button_was_pressed(), Ok(true));
+        assert!(light.borrow().is_on());
+        assert_eq!(control.redo_button_was_pressed(), Ok(true));
+        assert!(light.borrow().is_off());
+    }
+
+    #[test]
+    fn empty_slot_keeps_redo() {
+        let (mut control, light) = light_remote();
+        control.on_button_was_pressed(1).unwrap();
+        assert_eq!(control.undo_button_was_pressed(), Ok(true));
+        control.on_button_was_pressed(0).unwrap();
+        assert_eq!(control.redo_button_was_pressed(), Ok(true));
+        assert!(light.borrow().is_on());
+        assert_eq!(control.undo_button_was_pressed(), Ok(true));
+        assert_eq!(control.undo_button_was_pressed(), Ok(false));
+    }
+
+    #[test]
+    #[should_panic]
+    fn slot_out_of_range() {
+        let (mut control, _) = light_remote();
+        let _ = control.on_button_was_pressed(3);
+    }
+
+    #[test]
+    fn display() {
+        let (control, _) = light_remote();
+        assert_eq!(
+            control.to_string(),
+            "[slot 0] NoCommand / NoCommand\n\
+             [slot 1] LightOn / LightOff\n\
+             [slot 2] NoCommand / NoCommand\n"
+        );
+    }
+}
@@ -1,20 +1,38 @@
 use crate::command;
+use crate::history::History;
 
 pub struct SimpleRemoteControl {
     slot: Box<dyn command::Command>,
+    history: History,
 }
 
 impl SimpleRemoteControl {
     pub fn new(command: Box<dyn command::Command>) -> SimpleRemoteControl {
-        SimpleRemoteControl { slot: command }
+        SimpleRemoteControl {
+            slot: command,
+            history: History::default(),
+        }
     }
 
     pub fn set_command(&mut self, command: Box<dyn command::Command>) {
         self.slot = command;
     }
 
-    pub fn button_was_pressed(&mut self) {
-        self.slot.execute()
+    /// A press whose command fails is not recorded for undo.
+    pub fn button_was_pressed(&mut self) -> Result<(), command::CommandError> {
+        self.slot.execute()?;
+        self.history.record(self.slot.clone_box());
+        Ok(())
+    }
+
+    /// Undoes the most recent press; false if there is nothing to undo.
+    pub fn undo_button_was_pressed(&mut self) -> Result<bool, command::CommandError> {
+        self.history.undo()
+    }
+
+    /// Repeats the most recently undone press; false if there is nothing to redo.
+    pub fn redo_button_was_pressed(&mut self) -> Result<bool, command::CommandError> {
+        self.history.redo()
     }
 }
 
@@ -44,7 +62,57 @@ mod tests {
         let command = Box::new(command::LightOn::new(&light));
         let mut control = SimpleRemoteControl::new(command);
         assert!(light.borrow().is_off());
-        control.button_was_pressed();
+        control.button_was_pressed().unwrap();
+        assert!(light.borrow().is_on());
+    }
+
+    #[test]
+    fn undo_multiple_steps() {
+        let light = command::Light::for_int_mut();
+        let mut control = SimpleRemoteControl::new(Box::new(command::LightOn::new(&light)));
+        control.button_was_pressed().unwrap();
+        control.set_command(Box::new(command::LightOff::new(&light)));
+        control.button_was_pressed().unwrap();
+        assert!(light.borrow().is_off());
+        assert_eq!(control.undo_button_was_pressed(), Ok(true));
         assert!(light.borrow().is_on());
+        assert_eq!(control.undo_button_was_pressed(), Ok(true));
+        assert!(light.borrow().is_off());
+        assert_eq!(control.undo_button_was_pressed(), Ok(false));
+    }
+
+    #[test]
+    fn redo() {
+        let light = command::Light::for_int_mut();
+        let mut control = SimpleRemoteControl::new(Box::new(command::LightOn::new(&light)));
+        control.button_was_pressed().unwrap();
+        control.undo_button_was_pressed().unwrap();
+        assert_eq!(control.redo_button_was_pressed(), Ok(true));
+        assert!(light.borrow().is_on());
+        assert_eq!(control.redo_button_was_pressed(), Ok(false));
+    }
+
+    #[test]
+    fn new_press_invalidates_redo() {
+        let light = command::Light::for_int_mut();
+        let mut control = SimpleRemoteControl::new(Box::new(command::LightOn::new(&light)));
+        control.button_was_pressed().unwrap();
+        control.undo_button_was_pressed().unwrap();
+        control.button_was_pressed().unwrap();
+        assert_eq!(control.redo_button_was_pressed(), Ok(false));
+        assert!(light.borrow().is_on());
+        assert_eq!(control.undo_button_was_pressed(), Ok(true));
+        assert!(light.borrow().is_off());
+    }
+
+    #[test]
+    fn button_was_pressed_error() {
+        let light = command::Light::for_int_mut();
+        let mut control = SimpleRemoteControl::new(Box::new(command::LightOff::new(&light)));
+        assert_eq!(
+            control.button_was_pressed(),
+            Err(command::CommandError::AlreadyInState)
+        );
+        assert_eq!(control.undo_button_was_pressed(), Ok(false));
     }
 }
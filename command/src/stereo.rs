@@ -0,0 +1,224 @@
+use crate::command::{replace_state, Command, CommandError};
+use std::{cell::RefCell, rc::Rc};
+
+pub const MAX_VOLUME: u8 = 11;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Input {
+    #[default]
+    Cd,
+    Radio,
+    Aux,
+}
+
+#[derive(Default)]
+pub struct Stereo {
+    on: bool,
+    volume: u8,
+    input: Input,
+}
+
+impl Stereo {
+    pub fn is_on(&self) -> bool {
+        self.on
+    }
+
+    pub fn volume(&self) -> u8 {
+        self.volume
+    }
+
+    pub fn input(&self) -> Input {
+        self.input
+    }
+
+    pub fn for_int_mut() -> Rc<RefCell<Stereo>> {
+        Rc::new(RefCell::new(Stereo::default()))
+    }
+}
+
+#[derive(Clone)]
+pub struct StereoOn(Rc<RefCell<Stereo>>);
+
+impl StereoOn {
+    pub fn new(stereo: &Rc<RefCell<Stereo>>) -> StereoOn {
+        StereoOn(Rc::clone(stereo))
+    }
+}
+
+impl Command for StereoOn {
+    fn execute(&mut self) -> Result<(), CommandError> {
+        replace_state(&mut self.0.borrow_mut().on, true).map(|_| ())
+    }
+
+    fn undo(&mut self) -> Result<(), CommandError> {
+        replace_state(&mut self.0.borrow_mut().on, false).map(|_| ())
+    }
+}
+
+#[derive(Clone)]
+pub struct StereoOff(Rc<RefCell<Stereo>>);
+
+impl StereoOff {
+    pub fn new(stereo: &Rc<RefCell<Stereo>>) -> StereoOff {
+        StereoOff(Rc::clone(stereo))
+    }
+}
+
+impl Command for StereoOff {
+    fn execute(&mut self) -> Result<(), CommandError> {
+        replace_state(&mut self.0.borrow_mut().on, false).map(|_| ())
+    }
+
+    fn undo(&mut self) -> Result<(), CommandError> {
+        replace_state(&mut self.0.borrow_mut().on, true).map(|_| ())
+    }
+}
+
+/// Sets the volume, capped at `MAX_VOLUME`; undo restores the previous volume.
+#[derive(Clone)]
+pub struct StereoVolume {
+    stereo: Rc<RefCell<Stereo>>,
+    volume: u8,
+    previous: Option<u8>,
+}
+
+impl StereoVolume {
+    pub fn new(stereo: &Rc<RefCell<Stereo>>, volume: u8) -> StereoVolume {
+        StereoVolume {
+            stereo: Rc::clone(stereo),
+            volume: volume.min(MAX_VOLUME),
+            previous: None,
+        }
+    }
+}
+
+impl Command for StereoVolume {
+    fn execute(&mut self) -> Result<(), CommandError> {
+        self.previous = Some(replace_state(
+            &mut self.stereo.borrow_mut().volume,
+            self.volume,
+        )?);
+        Ok(())
+    }
+
+    /// Does nothing if the command was never executed.
+    fn undo(&mut self) -> Result<(), CommandError> {
+        match self.previous {
+            Some(previous) => {
+                replace_state(&mut self.stereo.borrow_mut().volume, previous).map(|_| ())
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+/// Switches the input; undo restores the previous input.
+#[derive(Clone)]
+pub struct StereoInput {
+    stereo: Rc<RefCell<Stereo>>,
+    input: Input,
+    previous: Option<Input>,
+}
+
+impl StereoInput {
+    pub fn new(stereo: &Rc<RefCell<Stereo>>, input: Input) -> StereoInput {
+        StereoInput {
+            stereo: Rc::clone(stereo),
+            input,
+            previous: None,
+        }
+    }
+}
+
+impl Command for StereoInput {
+    fn execute(&mut self) -> Result<(), CommandError> {
+        self.previous = Some(replace_state(
+            &mut self.stereo.borrow_mut().input,
+            self.input,
+        )?);
+        Ok(())
+    }
+
+    /// Does nothing if the command was never executed.
+    fn undo(&mut self) -> Result<(), CommandError> {
+        match self.previous {
+            Some(previous) => {
+                replace_state(&mut self.stereo.borrow_mut().input, previous).map(|_| ())
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::macro_command::MacroCommand;
+
+    #[test]
+    fn on_and_off() {
+        let stereo = Stereo::for_int_mut();
+        let mut on = StereoOn::new(&stereo);
+        on.execute().unwrap();
+        assert!(stereo.borrow().is_on());
+        assert_eq!(on.execute(), Err(CommandError::AlreadyInState));
+        StereoOff::new(&stereo).execute().unwrap();
+        assert!(!stereo.borrow().is_on());
+    }
+
+    #[test]
+    fn volume() {
+        let stereo = Stereo::for_int_mut();
+        let mut loud = StereoVolume::new(&stereo, 20);
+        loud.execute().unwrap();
+        assert_eq!(stereo.borrow().volume(), MAX_VOLUME);
+        loud.undo().unwrap();
+        assert_eq!(stereo.borrow().volume(), 0);
+    }
+
+    #[test]
+    fn input() {
+        let stereo = Stereo::for_int_mut();
+        let mut radio = StereoInput::new(&stereo, Input::Radio);
+        radio.execute().unwrap();
+        assert_eq!(stereo.borrow().input(), Input::Radio);
+        radio.undo().unwrap();
+        assert_eq!(stereo.borrow().input(), Input::Cd);
+    }
+
+    #[test]
+    fn listen_to_radio() {
+        let stereo = Stereo::for_int_mut();
+        let mut command = MacroCommand::new(vec![
+            Box::new(StereoOn::new(&stereo)),
+            Box::new(StereoInput::new(&stereo, Input::Radio)),
+            Box::new(StereoVolume::new(&stereo, 7)),
+        ]);
+        command.execute().unwrap();
+        assert!(stereo.borrow().is_on());
+        assert_eq!(stereo.borrow().input(), Input::Radio);
+        assert_eq!(stereo.borrow().volume(), 7);
+        command.undo().unwrap();
+        assert!(!stereo.borrow().is_on());
+        assert_eq!(stereo.borrow().input(), Input::Cd);
+        assert_eq!(stereo.borrow().volume(), 0);
+    }
+
+    #[test]
+    fn listen_to_radio_when_already_on() {
+        let stereo = Stereo::for_int_mut();
+        StereoOn::new(&stereo).execute().unwrap();
+        let mut command = MacroCommand::new(vec![
+            Box::new(StereoOn::new(&stereo)),
+            Box::new(StereoInput::new(&stereo, Input::Radio)),
+            Box::new(StereoVolume::new(&stereo, 7)),
+        ]);
+        command.execute().unwrap();
+        assert_eq!(stereo.borrow().input(), Input::Radio);
+        assert_eq!(stereo.borrow().volume(), 7);
+        command.undo().unwrap();
+        assert!(stereo.borrow().is_on());
+        assert_eq!(stereo.borrow().input(), Input::Cd);
+        assert_eq!(stereo.borrow().volume(), 0);
+    }
+}
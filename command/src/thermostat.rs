@@ -0,0 +1,91 @@
+use crate::command::{replace_state, Command, CommandError};
+use std::{cell::RefCell, rc::Rc};
+
+pub const DEFAULT_TARGET: i32 = 68;
+
+/// Target temperature in whole degrees Fahrenheit.
+pub struct Thermostat(i32);
+
+impl Default for Thermostat {
+    fn default() -> Self {
+        Thermostat(DEFAULT_TARGET)
+    }
+}
+
+impl Thermostat {
+    pub fn target(&self) -> i32 {
+        self.0
+    }
+
+    pub fn for_int_mut() -> Rc<RefCell<Thermostat>> {
+        Rc::new(RefCell::new(Thermostat::default()))
+    }
+}
+
+/// Sets the target temperature; undo restores the previous target.
+#[derive(Clone)]
+pub struct ThermostatSet {
+    thermostat: Rc<RefCell<Thermostat>>,
+    target: i32,
+    previous: Option<i32>,
+}
+
+impl ThermostatSet {
+    pub fn new(thermostat: &Rc<RefCell<Thermostat>>, target: i32) -> ThermostatSet {
+        ThermostatSet {
+            thermostat: Rc::clone(thermostat),
+            target,
+            previous: None,
+        }
+    }
+}
+
+impl Command for ThermostatSet {
+    fn execute(&mut self) -> Result<(), CommandError> {
+        self.previous = Some(replace_state(
+            &mut self.thermostat.borrow_mut().0,
+            self.target,
+        )?);
+        Ok(())
+    }
+
+    /// Does nothing if the command was never executed.
+    fn undo(&mut self) -> Result<(), CommandError> {
+        match self.previous {
+            Some(previous) => {
+                replace_state(&mut self.thermostat.borrow_mut().0, previous).map(|_| ())
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simple_remote_control::SimpleRemoteControl;
+
+    #[test]
+    fn execute() {
+        let thermostat = Thermostat::for_int_mut();
+        let mut command = ThermostatSet::new(&thermostat, 72);
+        command.execute().unwrap();
+        assert_eq!(thermostat.borrow().target(), 72);
+        assert_eq!(command.execute(), Err(CommandError::AlreadyInState));
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let thermostat = Thermostat::for_int_mut();
+        let mut control = SimpleRemoteControl::new(Box::new(ThermostatSet::new(&thermostat, 72)));
+        control.button_was_pressed().unwrap();
+        control.set_command(Box::new(ThermostatSet::new(&thermostat, 65)));
+        control.button_was_pressed().unwrap();
+        control.undo_button_was_pressed().unwrap();
+        assert_eq!(thermostat.borrow().target(), 72);
+        control.undo_button_was_pressed().unwrap();
+        assert_eq!(thermostat.borrow().target(), DEFAULT_TARGET);
+        control.redo_button_was_pressed().unwrap();
+        assert_eq!(thermostat.borrow().target(), 72);
+    }
+}
@@ -0,0 +1,347 @@
+use crate::clock::{Clock, SystemClock};
+use crate::feed::FIELDS;
+use crate::observer::Observer;
+use crate::Measurements;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const DEFAULT_MAX_FILES: usize = 7;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Observer that appends every update to a CSV file per UTC day, named
+/// `<prefix>-YYYY-MM-DD.csv`, in the layout `FeedReader` reads with `Format::Csv`.
+/// Only the newest `max_files` files are kept, and a day older than all of
+/// them is refused rather than written to a file that would be removed.
+pub struct CsvArchive {
+    directory: PathBuf,
+    prefix: String,
+    max_files: usize,
+    clock: Box<dyn Clock + Send>,
+    file: Option<(String, File)>,
+    error: Option<io::ErrorKind>,
+}
+
+impl CsvArchive {
+    pub fn new(directory: impl Into<PathBuf>, prefix: &str) -> CsvArchive {
+        CsvArchive {
+            directory: directory.into(),
+            prefix: String::from(prefix),
+            max_files: DEFAULT_MAX_FILES,
+            clock: Box::new(SystemClock),
+            file: None,
+            error: None,
+        }
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    pub fn max_files(&self) -> usize {
+        self.max_files
+    }
+
+    /// Names of this archive's files, oldest first.
+    pub fn files(&self) -> io::Result<Vec<String>> {
+        let mut names = Vec::new();
+        for entry in fs::read_dir(&self.directory)? {
+            let name = entry?.file_name().to_string_lossy().into_owned();
+            if self.is_archive_file(&name) {
+                names.push(name);
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    /// At least one file, the one being written, is always kept.
+    pub fn set_max_files(&mut self, max_files: usize) {
+        self.max_files = max_files.max(1);
+    }
+
+    /// Replaces the clock used to timestamp updates and to decide when to rotate.
+    pub fn set_clock(&mut self, clock: impl Clock + Send + 'static) {
+        self.clock = Box::new(clock);
+    }
+
+    /// Appends `measurements` to the file for the day of `timestamp`, starting a
+    /// new file and removing the oldest ones when the day changes. Fails with
+    /// `InvalidInput` if that day's file would be removed right away.
+    pub fn record(&mut self, measurements: &Measurements, timestamp: SystemTime) -> io::Result<()> {
+        let since_epoch = timestamp
+            .duration_since(UNIX_EPOCH)
+            .map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?;
+        let name = self.file_name(since_epoch.as_secs() / SECONDS_PER_DAY);
+        let file = match self.file.take() {
+            Some((current, file)) if current == name => file,
+            _ => {
+                self.check_kept(&name)?;
+                let file = self.open(&name)?;
+                self.remove_old_files()?;
+                file
+            }
+        };
+        let file = &mut self.file.insert((name, file)).1;
+        writeln!(
+            file,
+            "{},{},{},{}",
+            since_epoch.as_secs_f64(),
+            measurements.temperature(),
+            measurements.humidity(),
+            measurements.pressure()
+        )?;
+        file.flush()
+    }
+
+    /// The error of the most recent failed update, if any, clearing it.
+    pub fn take_error(&mut self) -> Option<io::ErrorKind> {
+        self.error.take()
+    }
+
+    fn file_name(&self, days: u64) -> String {
+        let (year, month, day) = civil_date(days);
+        format!("{}-{:04}-{:02}-{:02}.csv", self.prefix, year, month, day)
+    }
+
+    fn open(&self, name: &str) -> io::Result<File> {
+        fs::create_dir_all(&self.directory)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.directory.join(name))?;
+        if file.metadata()?.len() == 0 {
+            writeln!(file, "{}", FIELDS.join(","))?;
+        }
+        Ok(file)
+    }
+
+    fn is_archive_file(&self, name: &str) -> bool {
+        name.strip_prefix(self.prefix.as_str())
+            .and_then(|rest| rest.strip_prefix('-'))
+            .and_then(|rest| rest.strip_suffix(".csv"))
+            .is_some_and(|date| {
+                date.len() == 10
+                    && date.chars().enumerate().all(|(i, c)| {
+                        if i == 4 || i == 7 {
+                            c == '-'
+                        } else {
+                            c.is_ascii_digit()
+                        }
+                    })
+            })
+    }
+
+    /// Fails if `name` would be among the files `remove_old_files` removes.
+    fn check_kept(&self, name: &str) -> io::Result<()> {
+        let mut names = match self.files() {
+            Ok(names) => names,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(error) => return Err(error),
+        };
+        if !names.iter().any(|existing| existing == name) {
+            names.push(String::from(name));
+            names.sort();
+        }
+        let excess = names.len().saturating_sub(self.max_files);
+        if names[..excess].iter().any(|old| old == name) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{name} is older than every kept archive file"),
+            ));
+        }
+        Ok(())
+    }
+
+    fn remove_old_files(&self) -> io::Result<()> {
+        let names = self.files()?;
+        let excess = names.len().saturating_sub(self.max_files);
+        for name in &names[..excess] {
+            fs::remove_file(self.directory.join(name))?;
+        }
+        Ok(())
+    }
+}
+
+impl Observer for CsvArchive {
+    fn update(&mut self, metrics: &Measurements) {
+        // `Observer::update` cannot report failures; they are kept for
+        // `take_error` instead.
+        let timestamp = self.clock.now();
+        if let Err(error) = self.record(metrics, timestamp) {
+            self.error = Some(error.kind());
+        }
+    }
+}
+
+/// Converts days since the Unix epoch to a proleptic Gregorian (year, month, day).
+fn civil_date(days: u64) -> (u64, u64, u64) {
+    // Shift the epoch to 0000-03-01 so that leap days end each 400-year era.
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::feed::{FeedReader, Format};
+    use crate::measurements::fixture;
+    use crate::shared::{SharedSubject, SharedWeatherData};
+    use crate::subject::{Subject, WeatherData};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    fn directory(test: &str) -> PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("archive-{}-{}", test, std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        directory
+    }
+
+    #[test]
+    fn civil_dates() {
+        assert_eq!(civil_date(0), (1970, 1, 1));
+        assert_eq!(civil_date(59), (1970, 3, 1));
+        assert_eq!(civil_date(11_016), (2000, 2, 29));
+        assert_eq!(civil_date(19_675), (2023, 11, 14));
+    }
+
+    #[test]
+    fn appends_csv() {
+        let directory = directory("appends");
+        let clock = ManualClock::new(UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+        let archive = Rc::new(RefCell::new(CsvArchive::new(&directory, "station")));
+        archive.borrow_mut().set_clock(clock.clone());
+        let mut weather_data = WeatherData::default();
+        weather_data.register_observer(archive.clone());
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        clock.advance(Duration::from_secs(60));
+        weather_data.set_measurements(fixture(73.0, 50.1)).unwrap();
+        assert_eq!(archive.borrow_mut().take_error(), None);
+        let path = directory.join("station-2023-11-14.csv");
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "timestamp,temperature,humidity,pressure\n\
+             1700000000,72.3,50.1,29.92\n\
+             1700000060,73,50.1,29.92\n"
+        );
+        let readings: Vec<_> = FeedReader::open(&path, Format::Csv).unwrap().collect();
+        fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(readings.len(), 2);
+        assert_eq!(
+            readings[1].as_ref().unwrap().1.measurements(),
+            &fixture(73.0, 50.1)
+        );
+    }
+
+    #[test]
+    fn rotates_daily() {
+        let directory = directory("rotates");
+        let mut archive = CsvArchive::new(&directory, "station");
+        archive.set_max_files(2);
+        let day = Duration::from_secs(SECONDS_PER_DAY);
+        for days in 0..4 {
+            archive
+                .record(&fixture(70.0, 50.1), UNIX_EPOCH + day * days)
+                .unwrap();
+        }
+        fs::write(directory.join("notes.csv"), "kept").unwrap();
+        archive
+            .record(&fixture(71.0, 50.1), UNIX_EPOCH + day * 3)
+            .unwrap();
+        let files = archive.files().unwrap();
+        let latest = fs::read_to_string(directory.join("station-1970-01-04.csv")).unwrap();
+        let notes = directory.join("notes.csv").exists();
+        fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(
+            files,
+            vec!["station-1970-01-03.csv", "station-1970-01-04.csv"]
+        );
+        assert_eq!(latest.lines().count(), 3);
+        assert!(notes);
+    }
+
+    #[test]
+    fn refuses_day_older_than_kept_files() {
+        let directory = directory("refuses");
+        let mut archive = CsvArchive::new(&directory, "station");
+        archive.set_max_files(2);
+        let day = Duration::from_secs(SECONDS_PER_DAY);
+        for days in [2, 3] {
+            archive
+                .record(&fixture(70.0, 50.1), UNIX_EPOCH + day * days)
+                .unwrap();
+        }
+        let error = archive
+            .record(&fixture(70.0, 50.1), UNIX_EPOCH + day)
+            .unwrap_err();
+        let files = archive.files().unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(
+            files,
+            vec!["station-1970-01-03.csv", "station-1970-01-04.csv"]
+        );
+    }
+
+    #[test]
+    fn shared_archive() {
+        let directory = directory("shared");
+        let archive = Arc::new(Mutex::new(CsvArchive::new(&directory, "station")));
+        archive
+            .lock()
+            .unwrap()
+            .set_clock(ManualClock::new(UNIX_EPOCH));
+        let weather_data = SharedWeatherData::default();
+        weather_data.register_observer(archive.clone());
+        weather_data.set_measurements(fixture(70.0, 50.1)).unwrap();
+        let files = archive.lock().unwrap().files().unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(files, vec!["station-1970-01-01.csv"]);
+    }
+
+    #[test]
+    fn reopens_existing_file() {
+        let directory = directory("reopens");
+        let timestamp = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        CsvArchive::new(&directory, "station")
+            .record(&fixture(70.0, 50.1), timestamp)
+            .unwrap();
+        CsvArchive::new(&directory, "station")
+            .record(&fixture(71.0, 50.1), timestamp)
+            .unwrap();
+        let contents = fs::read_to_string(directory.join("station-2023-11-14.csv")).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(contents.lines().count(), 3);
+    }
+
+    #[test]
+    fn update_keeps_error() {
+        let directory = directory("error");
+        fs::write(&directory, "not a directory").unwrap();
+        let mut archive = CsvArchive::new(&directory, "station");
+        archive.set_clock(ManualClock::default());
+        archive.update(&fixture(70.0, 50.1));
+        fs::remove_file(&directory).unwrap();
+        assert!(archive.take_error().is_some());
+        assert_eq!(archive.take_error(), None);
+    }
+}
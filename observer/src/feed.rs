@@ -0,0 +1,397 @@
+use crate::history::Reading;
+use crate::measurements::{MeasurementsError, PressureUnit, TemperatureUnit};
+use crate::subject::{NotifyError, WeatherData};
+use crate::Measurements;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Lines};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+pub(crate) const FIELDS: [&str; 4] = ["timestamp", "temperature", "humidity", "pressure"];
+
+/// Layout of a recorded station feed. Both carry a Unix `timestamp` in seconds,
+/// `temperature` in Fahrenheit, `humidity` in percent and `pressure` in inches
+/// of mercury.
+///
+/// CSV feeds start with a header row naming the columns, in any order. JSON-lines
+/// feeds hold one object per line whose fields are numbers, e.g.
+/// `{"timestamp": 1700000000, "temperature": 72.3, "humidity": 50.1, "pressure": 29.92}`;
+/// other keys may hold any JSON value and are ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    JsonLines,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RecordError {
+    MissingHeader,
+    MissingField(&'static str),
+    InvalidNumber { field: String, value: String },
+    InvalidTimestamp(f64),
+    Malformed,
+    Measurements(MeasurementsError),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum FeedError {
+    Io(io::ErrorKind),
+    /// `line` is 1-based and counts the CSV header and blank lines.
+    Record {
+        line: usize,
+        error: RecordError,
+    },
+    Notify {
+        line: usize,
+        error: NotifyError,
+    },
+}
+
+pub struct FeedReader<R> {
+    lines: Lines<R>,
+    format: Format,
+    columns: Option<Vec<String>>,
+    line: usize,
+}
+
+impl FeedReader<BufReader<File>> {
+    pub fn open(path: impl AsRef<Path>, format: Format) -> io::Result<FeedReader<BufReader<File>>> {
+        Ok(FeedReader::new(BufReader::new(File::open(path)?), format))
+    }
+}
+
+impl<R: BufRead> FeedReader<R> {
+    pub fn new(reader: R, format: Format) -> FeedReader<R> {
+        FeedReader {
+            lines: reader.lines(),
+            format,
+            columns: None,
+            line: 0,
+        }
+    }
+
+    /// Feeds every record to `weather_data`, stopping at the first invalid row.
+    /// Returns the number of records published.
+    pub fn publish_to(self, weather_data: &mut WeatherData) -> Result<usize, FeedError> {
+        let mut count = 0;
+        for record in self {
+            let (line, reading) = record?;
+            weather_data
+                .set_measurements_at(reading.measurements().clone(), reading.timestamp())
+                .map_err(|error| FeedError::Notify { line, error })?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    fn parse_csv(&mut self, text: &str) -> Result<Option<Reading>, RecordError> {
+        let values = text.split(',').map(str::trim);
+        let Some(columns) = &self.columns else {
+            let columns: Vec<String> = values.map(String::from).collect();
+            if !FIELDS
+                .iter()
+                .all(|field| columns.iter().any(|column| column == field))
+            {
+                return Err(RecordError::MissingHeader);
+            }
+            self.columns = Some(columns);
+            return Ok(None);
+        };
+        let values: Vec<&str> = values.collect();
+        if values.len() != columns.len() {
+            return Err(RecordError::Malformed);
+        }
+        let fields = columns.iter().zip(values).map(|(column, value)| {
+            let number = value.parse::<f64>().map_err(|_| String::from(value));
+            (column.as_str(), number)
+        });
+        parse_fields(fields).map(Some)
+    }
+
+    fn parse_json(&self, text: &str) -> Result<Option<Reading>, RecordError> {
+        let object: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_str(text).map_err(|_| RecordError::Malformed)?;
+        // Values that are not numbers, such as the string "72.3", are reported
+        // as `InvalidNumber` with their JSON text.
+        let fields = object.iter().map(|(key, value)| {
+            (
+                key.as_str(),
+                value.as_f64().ok_or_else(|| value.to_string()),
+            )
+        });
+        parse_fields(fields).map(Some)
+    }
+}
+
+/// Each field's value is its number, or the text that is not one.
+fn parse_fields<'a>(
+    fields: impl IntoIterator<Item = (&'a str, Result<f64, String>)>,
+) -> Result<Reading, RecordError> {
+    let mut values = [None; 4];
+    for (key, value) in fields {
+        if let Some(index) = FIELDS.iter().position(|field| *field == key) {
+            let number = value.map_err(|value| RecordError::InvalidNumber {
+                field: String::from(key),
+                value,
+            })?;
+            values[index] = Some(number);
+        }
+    }
+    let [timestamp, temperature, humidity, pressure] = values;
+    let timestamp = timestamp.ok_or(RecordError::MissingField("timestamp"))?;
+    let timestamp = Duration::try_from_secs_f64(timestamp)
+        .map(|duration| SystemTime::UNIX_EPOCH + duration)
+        .map_err(|_| RecordError::InvalidTimestamp(timestamp))?;
+    let measurements = Measurements::builder()
+        .temperature(
+            temperature.ok_or(RecordError::MissingField("temperature"))?,
+            TemperatureUnit::Fahrenheit,
+        )
+        .humidity(humidity.ok_or(RecordError::MissingField("humidity"))?)
+        .pressure(
+            pressure.ok_or(RecordError::MissingField("pressure"))?,
+            PressureUnit::InchesOfMercury,
+        )
+        .build()
+        .map_err(RecordError::Measurements)?;
+    Ok(Reading::new(timestamp, measurements))
+}
+
+impl<R: BufRead> Iterator for FeedReader<R> {
+    /// Each reading is paired with the line it was parsed from.
+    type Item = Result<(usize, Reading), FeedError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let text = match self.lines.next()? {
+                Ok(text) => text,
+                Err(error) => return Some(Err(FeedError::Io(error.kind()))),
+            };
+            self.line += 1;
+            let text = text.trim();
+            if text.is_empty() {
+                continue;
+            }
+            let record = match self.format {
+                Format::Csv => self.parse_csv(text),
+                Format::JsonLines => self.parse_json(text),
+            };
+            match record {
+                Ok(Some(reading)) => return Some(Ok((self.line, reading))),
+                Ok(None) => continue,
+                Err(error) => {
+                    return Some(Err(FeedError::Record {
+                        line: self.line,
+                        error,
+                    }))
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::observer::{LoggingElement, StatisticsDisplay};
+    use crate::subject::Subject;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    const CSV: &str = "\
+timestamp,temperature,humidity,pressure
+1700000000,70.0,40.0,29.9
+
+1700003600,74.0,50.0,30.0
+";
+
+    const JSON_LINES: &str = r#"
+{"timestamp": 1700000000, "temperature": 70.0, "humidity": 40.0, "pressure": 29.9}
+{"pressure": 30.0, "humidity": 50.0, "temperature": 74.0, "timestamp": 1700003600}
+"#;
+
+    fn readings(feed: &str, format: Format) -> Result<Vec<(usize, Reading)>, FeedError> {
+        FeedReader::new(feed.as_bytes(), format).collect()
+    }
+
+    #[test]
+    fn csv() {
+        let readings = readings(CSV, Format::Csv).unwrap();
+        assert_eq!(readings.len(), 2);
+        let (line, reading) = &readings[1];
+        assert_eq!(*line, 4);
+        assert_eq!(
+            reading.timestamp(),
+            SystemTime::UNIX_EPOCH + Duration::from_secs(1700003600)
+        );
+        assert_eq!(reading.measurements().temperature(), 74.0);
+        assert_eq!(reading.measurements().humidity(), 50.0);
+        assert_eq!(reading.measurements().pressure(), 30.0);
+    }
+
+    #[test]
+    fn csv_reordered_columns() {
+        let feed = "humidity,pressure,timestamp,temperature\n40,29.9,0,70\n";
+        let readings = readings(feed, Format::Csv).unwrap();
+        assert_eq!(readings[0].1.measurements().temperature(), 70.0);
+    }
+
+    #[test]
+    fn csv_missing_header() {
+        let feed = "1700000000,70.0,40.0,29.9\n";
+        assert_eq!(
+            readings(feed, Format::Csv),
+            Err(FeedError::Record {
+                line: 1,
+                error: RecordError::MissingHeader,
+            })
+        );
+    }
+
+    #[test]
+    fn csv_wrong_column_count() {
+        let feed = "timestamp,temperature,humidity,pressure\n1700000000,70.0,40.0\n";
+        assert_eq!(
+            readings(feed, Format::Csv),
+            Err(FeedError::Record {
+                line: 2,
+                error: RecordError::Malformed,
+            })
+        );
+    }
+
+    #[test]
+    fn csv_invalid_number() {
+        let feed = "timestamp,temperature,humidity,pressure\n1700000000,warm,40.0,29.9\n";
+        assert_eq!(
+            readings(feed, Format::Csv),
+            Err(FeedError::Record {
+                line: 2,
+                error: RecordError::InvalidNumber {
+                    field: String::from("temperature"),
+                    value: String::from("warm"),
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn json_lines() {
+        let readings = readings(JSON_LINES, Format::JsonLines).unwrap();
+        assert_eq!(readings.len(), 2);
+        assert_eq!(readings[0].0, 2);
+        assert_eq!(readings[1].1.measurements().humidity(), 50.0);
+    }
+
+    #[test]
+    fn json_lines_missing_field() {
+        let feed = r#"{"timestamp": 0, "temperature": 70.0, "humidity": 40.0}"#;
+        assert_eq!(
+            readings(feed, Format::JsonLines),
+            Err(FeedError::Record {
+                line: 1,
+                error: RecordError::MissingField("pressure"),
+            })
+        );
+    }
+
+    #[test]
+    fn json_lines_malformed() {
+        let feed = r#"["timestamp", 0]"#;
+        assert_eq!(
+            readings(feed, Format::JsonLines),
+            Err(FeedError::Record {
+                line: 1,
+                error: RecordError::Malformed,
+            })
+        );
+    }
+
+    #[test]
+    fn json_lines_nested_values() {
+        let feed = r#"{"station": {"name": "north, roof", "tags": [1, 2]}, "timestamp": 0, "temperature": 70.0, "humidity": 40.0, "pressure": 29.9}"#;
+        let readings = readings(feed, Format::JsonLines).unwrap();
+        assert_eq!(readings[0].1.measurements().temperature(), 70.0);
+    }
+
+    #[test]
+    fn json_lines_invalid_number() {
+        let feed =
+            r#"{"timestamp": 0, "temperature": "70, warm", "humidity": 40.0, "pressure": 29.9}"#;
+        assert_eq!(
+            readings(feed, Format::JsonLines),
+            Err(FeedError::Record {
+                line: 1,
+                error: RecordError::InvalidNumber {
+                    field: String::from("temperature"),
+                    value: String::from(r#""70, warm""#),
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_measurements() {
+        let feed = r#"{"timestamp": 0, "temperature": 70.0, "humidity": 140.0, "pressure": 29.9}"#;
+        assert_eq!(
+            readings(feed, Format::JsonLines),
+            Err(FeedError::Record {
+                line: 1,
+                error: RecordError::Measurements(MeasurementsError::HumidityOutOfRange(140.0)),
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_timestamp() {
+        let feed = r#"{"timestamp": -1, "temperature": 70.0, "humidity": 40.0, "pressure": 29.9}"#;
+        assert_eq!(
+            readings(feed, Format::JsonLines),
+            Err(FeedError::Record {
+                line: 1,
+                error: RecordError::InvalidTimestamp(-1.0),
+            })
+        );
+    }
+
+    #[test]
+    fn publish_to() {
+        let mut weather_data = WeatherData::with_history(10);
+        let statistics_display = Rc::new(RefCell::new(StatisticsDisplay::default()));
+        weather_data.register_observer(statistics_display.clone());
+        let count = FeedReader::new(CSV.as_bytes(), Format::Csv)
+            .publish_to(&mut weather_data)
+            .unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(weather_data.history().len(), 2);
+        assert_eq!(statistics_display.borrow().get_logs().len(), 2);
+        assert_eq!(
+            statistics_display.borrow().temperature().average(),
+            Some(72.0)
+        );
+    }
+
+    #[test]
+    fn open() {
+        let path = std::env::temp_dir().join(format!("feed-{}.jsonl", std::process::id()));
+        std::fs::write(&path, JSON_LINES).unwrap();
+        let readings: Vec<_> = FeedReader::open(&path, Format::JsonLines)
+            .unwrap()
+            .collect();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(readings.len(), 2);
+    }
+
+    #[test]
+    fn publish_to_stops_at_invalid_row() {
+        let feed = format!("{}1700007200,74.0,,30.0\n", CSV);
+        let mut weather_data = WeatherData::default();
+        let statistics_display = Rc::new(RefCell::new(StatisticsDisplay::default()));
+        weather_data.register_observer(statistics_display.clone());
+        let error = FeedReader::new(feed.as_bytes(), Format::Csv)
+            .publish_to(&mut weather_data)
+            .unwrap_err();
+        assert!(matches!(error, FeedError::Record { line: 5, .. }));
+        assert_eq!(statistics_display.borrow().get_logs().len(), 2);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::Measurements;
+use std::ops::BitOr;
+
+/// Set of measurement fields a `Filter` watches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fields(u8);
+
+impl Fields {
+    pub const TEMPERATURE: Fields = Fields(0b001);
+    pub const HUMIDITY: Fields = Fields(0b010);
+    pub const PRESSURE: Fields = Fields(0b100);
+    pub const ALL: Fields = Fields(0b111);
+
+    pub fn contains(self, other: Fields) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    fn values(self, measurements: &Measurements) -> impl Iterator<Item = f64> + '_ {
+        [
+            (Fields::TEMPERATURE, measurements.temperature()),
+            (Fields::HUMIDITY, measurements.humidity()),
+            (Fields::PRESSURE, measurements.pressure()),
+        ]
+        .into_iter()
+        .filter(move |(field, _)| self.contains(*field))
+        .map(|(_, value)| value)
+    }
+}
+
+impl BitOr for Fields {
+    type Output = Fields;
+
+    fn bitor(self, rhs: Fields) -> Fields {
+        Fields(self.0 | rhs.0)
+    }
+}
+
+/// Predicate deciding whether a subscriber is notified of a set of measurements.
+pub struct Filter(Box<dyn FnMut(&Measurements) -> bool>);
+
+impl Filter {
+    pub fn new(predicate: impl FnMut(&Measurements) -> bool + 'static) -> Filter {
+        Filter(Box::new(predicate))
+    }
+
+    /// Passes the first reading, then only readings where one of `fields` differs
+    /// from the last reading this filter passed.
+    pub fn changed(fields: Fields) -> Filter {
+        Filter::changed_by(fields, 0.0)
+    }
+
+    /// Like `Filter::changed`, but a field must move by more than `threshold`,
+    /// expressed in the field's `Measurements` accessor unit.
+    pub fn changed_by(fields: Fields, threshold: f64) -> Filter {
+        let mut last: Option<Measurements> = None;
+        Filter::new(move |measurements| {
+            let changed = match &last {
+                None => true,
+                Some(last) => fields
+                    .values(last)
+                    .zip(fields.values(measurements))
+                    .any(|(last, current)| (current - last).abs() > threshold),
+            };
+            if changed {
+                last = Some(measurements.clone());
+            }
+            changed
+        })
+    }
+
+    pub(crate) fn matches(&mut self, measurements: &Measurements) -> bool {
+        (self.0)(measurements)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::measurements::fixture;
+
+    #[test]
+    fn fields_contains() {
+        let fields = Fields::TEMPERATURE | Fields::HUMIDITY;
+        assert!(fields.contains(Fields::TEMPERATURE));
+        assert!(fields.contains(Fields::HUMIDITY));
+        assert!(!fields.contains(Fields::PRESSURE));
+        assert!(Fields::ALL.contains(fields));
+    }
+
+    #[test]
+    fn new() {
+        let mut filter = Filter::new(|measurements| measurements.humidity() > 60.0);
+        assert!(!filter.matches(&fixture(80.0, 50.0)));
+        assert!(filter.matches(&fixture(80.0, 65.0)));
+    }
+
+    #[test]
+    fn changed() {
+        let mut filter = Filter::changed(Fields::HUMIDITY);
+        assert!(filter.matches(&fixture(80.0, 50.0)));
+        assert!(!filter.matches(&fixture(82.0, 50.0)));
+        assert!(filter.matches(&fixture(82.0, 51.0)));
+    }
+
+    #[test]
+    fn changed_by() {
+        let mut filter = Filter::changed_by(Fields::TEMPERATURE, 2.0);
+        assert!(filter.matches(&fixture(80.0, 50.0)));
+        assert!(!filter.matches(&fixture(81.5, 50.0)));
+        assert!(!filter.matches(&fixture(82.0, 50.0)));
+        assert!(filter.matches(&fixture(82.5, 50.0)));
+        assert!(!filter.matches(&fixture(81.0, 50.0)));
+    }
+}
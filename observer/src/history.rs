@@ -0,0 +1,139 @@
+use crate::Measurements;
+use std::collections::VecDeque;
+use std::time::SystemTime;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reading {
+    timestamp: SystemTime,
+    measurements: Measurements,
+}
+
+impl Reading {
+    pub fn new(timestamp: SystemTime, measurements: Measurements) -> Reading {
+        Reading {
+            timestamp,
+            measurements,
+        }
+    }
+
+    pub fn timestamp(&self) -> SystemTime {
+        self.timestamp
+    }
+
+    pub fn measurements(&self) -> &Measurements {
+        &self.measurements
+    }
+}
+
+/// Ring buffer keeping the most recent `capacity` readings, oldest first.
+#[derive(Debug, Default)]
+pub struct History {
+    readings: VecDeque<Reading>,
+    capacity: usize,
+}
+
+impl History {
+    pub fn with_capacity(capacity: usize) -> History {
+        History {
+            readings: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, reading: Reading) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.readings.len() == self.capacity {
+            self.readings.pop_front();
+        }
+        self.readings.push_back(reading);
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.readings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.readings.is_empty()
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Reading> + ExactSizeIterator {
+        self.readings.iter()
+    }
+
+    /// The last `count` readings, oldest first.
+    pub fn last(&self, count: usize) -> impl Iterator<Item = &Reading> {
+        self.readings
+            .iter()
+            .skip(self.readings.len().saturating_sub(count))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::measurements::fixture;
+    use std::time::Duration;
+
+    fn reading(seconds: u64) -> Reading {
+        Reading::new(
+            SystemTime::UNIX_EPOCH + Duration::from_secs(seconds),
+            fixture(72.3, 50.1),
+        )
+    }
+
+    fn timestamps<'a>(readings: impl Iterator<Item = &'a Reading>) -> Vec<SystemTime> {
+        readings.map(Reading::timestamp).collect()
+    }
+
+    #[test]
+    fn push_within_capacity() {
+        let mut history = History::with_capacity(3);
+        history.push(reading(1));
+        history.push(reading(2));
+        assert_eq!(history.len(), 2);
+        assert_eq!(
+            timestamps(history.iter()),
+            timestamps([reading(1), reading(2)].iter())
+        );
+    }
+
+    #[test]
+    fn push_evicts_oldest() {
+        let mut history = History::with_capacity(2);
+        for seconds in 1..=4 {
+            history.push(reading(seconds));
+        }
+        assert_eq!(history.len(), 2);
+        assert_eq!(
+            timestamps(history.iter()),
+            timestamps([reading(3), reading(4)].iter())
+        );
+    }
+
+    #[test]
+    fn zero_capacity() {
+        let mut history = History::default();
+        history.push(reading(1));
+        assert!(history.is_empty());
+    }
+
+    #[test]
+    fn last() {
+        let mut history = History::with_capacity(5);
+        for seconds in 1..=4 {
+            history.push(reading(seconds));
+        }
+        assert_eq!(
+            timestamps(history.last(2)),
+            timestamps([reading(3), reading(4)].iter())
+        );
+        assert_eq!(history.last(10).count(), 4);
+        assert_eq!(history.last(0).count(), 0);
+    }
+}
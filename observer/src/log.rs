@@ -0,0 +1,341 @@
+use crate::clock::{Clock, SystemClock};
+use serde_json::{json, Map};
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::SystemTime;
+
+pub const DEFAULT_RETENTION: Retention = Retention::Last(1000);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(f64),
+    Text(String),
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Number(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::Text(String::from(value))
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::Text(value)
+    }
+}
+
+/// A single record logged by a display: when, by whom, a human-readable
+/// summary and the values behind it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogEntry {
+    timestamp: SystemTime,
+    display: String,
+    summary: String,
+    fields: Vec<(&'static str, Value)>,
+}
+
+impl LogEntry {
+    pub fn new(timestamp: SystemTime, display: &str, summary: String) -> LogEntry {
+        LogEntry {
+            timestamp,
+            display: String::from(display),
+            summary,
+            fields: Vec::new(),
+        }
+    }
+
+    pub fn with_field(mut self, name: &'static str, value: impl Into<Value>) -> LogEntry {
+        self.fields.push((name, value.into()));
+        self
+    }
+
+    pub fn timestamp(&self) -> SystemTime {
+        self.timestamp
+    }
+
+    pub fn display(&self) -> &str {
+        &self.display
+    }
+
+    pub fn summary(&self) -> &str {
+        &self.summary
+    }
+
+    pub fn fields(&self) -> &[(&'static str, Value)] {
+        &self.fields
+    }
+
+    pub fn field(&self, name: &str) -> Option<&Value> {
+        self.fields
+            .iter()
+            .find(|(field, _)| *field == name)
+            .map(|(_, value)| value)
+    }
+
+    pub fn number(&self, name: &str) -> Option<f64> {
+        match self.field(name)? {
+            Value::Number(value) => Some(*value),
+            Value::Text(_) => None,
+        }
+    }
+}
+
+pub trait LogFormatter {
+    fn format(&self, entry: &LogEntry) -> String;
+}
+
+/// Formats an entry as its human-readable summary.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TextFormatter;
+
+impl LogFormatter for TextFormatter {
+    fn format(&self, entry: &LogEntry) -> String {
+        entry.summary.clone()
+    }
+}
+
+/// Formats an entry as a single-line JSON object, with the timestamp in Unix seconds.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct JsonFormatter;
+
+impl LogFormatter for JsonFormatter {
+    fn format(&self, entry: &LogEntry) -> String {
+        let seconds = entry
+            .timestamp
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0.0, |duration| duration.as_secs_f64());
+        let fields: Map<String, serde_json::Value> = entry
+            .fields
+            .iter()
+            .map(|(name, value)| {
+                let value = match value {
+                    // Non-finite numbers have no JSON form and become null.
+                    Value::Number(value) => serde_json::Value::from(*value),
+                    Value::Text(value) => serde_json::Value::from(value.as_str()),
+                };
+                (String::from(*name), value)
+            })
+            .collect();
+        json!({
+            "timestamp": seconds,
+            "display": entry.display,
+            "summary": entry.summary,
+            "fields": fields,
+        })
+        .to_string()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Retention {
+    Unbounded,
+    /// Keep only the most recent `n` entries.
+    Last(usize),
+}
+
+/// Log of a single display, which names itself in every entry it records and
+/// stamps it with its clock.
+pub struct Logs {
+    display: String,
+    entries: VecDeque<LogEntry>,
+    retention: Retention,
+    clock: Box<dyn Clock + Send>,
+}
+
+impl fmt::Debug for Logs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Logs")
+            .field("display", &self.display)
+            .field("entries", &self.entries)
+            .field("retention", &self.retention)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Logs {
+    pub fn new(display: &str) -> Logs {
+        Logs {
+            display: String::from(display),
+            entries: VecDeque::new(),
+            retention: DEFAULT_RETENTION,
+            clock: Box::new(SystemClock),
+        }
+    }
+
+    pub fn display(&self) -> &str {
+        &self.display
+    }
+
+    /// Renames the display in entries recorded from now on.
+    pub fn set_display(&mut self, display: &str) {
+        self.display = String::from(display);
+    }
+
+    pub fn retention(&self) -> Retention {
+        self.retention
+    }
+
+    /// Drops the oldest entries right away if they exceed the new policy.
+    pub fn set_retention(&mut self, retention: Retention) {
+        self.retention = retention;
+        self.enforce_retention();
+    }
+
+    /// Replaces the clock used to timestamp entries recorded from now on.
+    pub fn set_clock(&mut self, clock: impl Clock + Send + 'static) {
+        self.clock = Box::new(clock);
+    }
+
+    /// Records an entry stamped with the current time and this log's display.
+    pub fn record(&mut self, summary: String, fields: Vec<(&'static str, Value)>) {
+        let mut entry = LogEntry::new(self.clock.now(), &self.display, summary);
+        entry.fields = fields;
+        self.push(entry);
+    }
+
+    pub fn push(&mut self, entry: LogEntry) {
+        self.entries.push_back(entry);
+        self.enforce_retention();
+    }
+
+    fn enforce_retention(&mut self) {
+        if let Retention::Last(capacity) = self.retention {
+            while self.entries.len() > capacity {
+                self.entries.pop_front();
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &LogEntry> + ExactSizeIterator {
+        self.entries.iter()
+    }
+
+    pub fn last(&self) -> Option<&LogEntry> {
+        self.entries.back()
+    }
+
+    pub fn format(&self, formatter: &dyn LogFormatter) -> Vec<String> {
+        self.entries
+            .iter()
+            .map(|entry| formatter.format(entry))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use std::time::Duration;
+
+    fn entry() -> LogEntry {
+        LogEntry::new(
+            SystemTime::UNIX_EPOCH + Duration::from_millis(1500),
+            "current_conditions",
+            String::from("Current conditions: 72.3F degrees and 50.1% humidity"),
+        )
+        .with_field("temperature", 72.3)
+        .with_field("humidity", 50.1)
+    }
+
+    #[test]
+    fn fields() {
+        let entry = entry().with_field("forecast", "improving");
+        assert_eq!(entry.number("temperature"), Some(72.3));
+        assert_eq!(entry.field("forecast"), Some(&Value::from("improving")));
+        assert_eq!(entry.number("forecast"), None);
+        assert_eq!(entry.field("pressure"), None);
+    }
+
+    #[test]
+    fn text_formatter() {
+        assert_eq!(
+            TextFormatter.format(&entry()),
+            "Current conditions: 72.3F degrees and 50.1% humidity"
+        );
+    }
+
+    #[test]
+    fn json_formatter() {
+        assert_eq!(
+            JsonFormatter.format(&entry()),
+            concat!(
+                r#"{"timestamp":1.5,"display":"current_conditions","#,
+                r#""summary":"Current conditions: 72.3F degrees and 50.1% humidity","#,
+                r#""fields":{"temperature":72.3,"humidity":50.1}}"#,
+            )
+        );
+    }
+
+    #[test]
+    fn json_formatter_escapes() {
+        let entry = LogEntry::new(SystemTime::UNIX_EPOCH, "a \"b\"\n", String::new())
+            .with_field("value", f64::NAN);
+        assert_eq!(
+            JsonFormatter.format(&entry),
+            r#"{"timestamp":0.0,"display":"a \"b\"\n","summary":"","fields":{"value":null}}"#
+        );
+    }
+
+    #[test]
+    fn record() {
+        let mut logs = Logs::new("forecast");
+        logs.set_clock(ManualClock::new(
+            SystemTime::UNIX_EPOCH + Duration::from_secs(60),
+        ));
+        logs.record(
+            String::from("Forecast: More of the same"),
+            vec![("forecast", Value::from("same"))],
+        );
+        let entry = logs.last().unwrap();
+        assert_eq!(
+            entry.timestamp(),
+            SystemTime::UNIX_EPOCH + Duration::from_secs(60)
+        );
+        assert_eq!(entry.display(), "forecast");
+        assert_eq!(entry.summary(), "Forecast: More of the same");
+        assert_eq!(entry.fields(), &[("forecast", Value::from("same"))]);
+    }
+
+    #[test]
+    fn retention() {
+        let mut logs = Logs::new("test");
+        assert_eq!(logs.retention(), DEFAULT_RETENTION);
+        logs.set_retention(Retention::Last(2));
+        for index in 0..5 {
+            logs.record(format!("{index}"), Vec::new());
+        }
+        assert_eq!(logs.format(&TextFormatter), vec!["3", "4"]);
+        logs.set_retention(Retention::Last(1));
+        assert_eq!(logs.format(&TextFormatter), vec!["4"]);
+        logs.set_retention(Retention::Unbounded);
+        for index in 5..2000 {
+            logs.record(format!("{index}"), Vec::new());
+        }
+        assert_eq!(logs.len(), 1996);
+    }
+
+    #[test]
+    fn set_display() {
+        let mut logs = Logs::new("a");
+        logs.record(String::new(), Vec::new());
+        logs.set_display("b");
+        logs.record(String::new(), Vec::new());
+        let displays: Vec<_> = logs.iter().map(LogEntry::display).collect();
+        assert_eq!(displays, vec!["a", "b"]);
+    }
+}
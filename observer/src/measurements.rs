@@ -0,0 +1,325 @@
+use std::ops::RangeInclusive;
+
+/// Temperatures outside this range (in Fahrenheit) are rejected as sensor faults.
+const TEMPERATURE_RANGE: RangeInclusive<f64> = -130.0..=140.0;
+
+const HUMIDITY_RANGE: RangeInclusive<f64> = 0.0..=100.0;
+
+/// Pressures outside this range (in inches of mercury) are rejected as sensor faults.
+const PRESSURE_RANGE: RangeInclusive<f64> = 25.0..=32.5;
+
+const HECTOPASCALS_PER_INCH_OF_MERCURY: f64 = 33.8639;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemperatureUnit {
+    Fahrenheit,
+    Celsius,
+}
+
+impl TemperatureUnit {
+    fn normalize(self, value: f64) -> f64 {
+        match self {
+            TemperatureUnit::Fahrenheit => value,
+            TemperatureUnit::Celsius => value * 9.0 / 5.0 + 32.0,
+        }
+    }
+
+    fn convert(self, value: f64) -> f64 {
+        match self {
+            TemperatureUnit::Fahrenheit => value,
+            TemperatureUnit::Celsius => (value - 32.0) * 5.0 / 9.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PressureUnit {
+    InchesOfMercury,
+    Hectopascals,
+}
+
+impl PressureUnit {
+    fn normalize(self, value: f64) -> f64 {
+        match self {
+            PressureUnit::InchesOfMercury => value,
+            PressureUnit::Hectopascals => value / HECTOPASCALS_PER_INCH_OF_MERCURY,
+        }
+    }
+
+    fn convert(self, value: f64) -> f64 {
+        match self {
+            PressureUnit::InchesOfMercury => value,
+            PressureUnit::Hectopascals => value * HECTOPASCALS_PER_INCH_OF_MERCURY,
+        }
+    }
+}
+
+/// Out-of-range values are reported in Fahrenheit, percent and inches of mercury.
+#[derive(Debug, PartialEq)]
+pub enum MeasurementsError {
+    MissingTemperature,
+    MissingHumidity,
+    MissingPressure,
+    TemperatureOutOfRange(f64),
+    HumidityOutOfRange(f64),
+    PressureOutOfRange(f64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurements {
+    temperature: f64,
+    humidity: f64,
+    pressure: f64,
+}
+
+impl Measurements {
+    pub fn builder() -> MeasurementsBuilder {
+        MeasurementsBuilder::default()
+    }
+
+    /// Field-wise mean, or `None` when `measurements` is empty.
+    pub fn average<'a>(
+        measurements: impl IntoIterator<Item = &'a Measurements>,
+    ) -> Option<Measurements> {
+        let mut count = 0;
+        let mut sum = Measurements {
+            temperature: 0.0,
+            humidity: 0.0,
+            pressure: 0.0,
+        };
+        for measurements in measurements {
+            sum.temperature += measurements.temperature;
+            sum.humidity += measurements.humidity;
+            sum.pressure += measurements.pressure;
+            count += 1;
+        }
+        (count > 0).then(|| {
+            let count = f64::from(count);
+            Measurements {
+                temperature: sum.temperature / count,
+                humidity: sum.humidity / count,
+                pressure: sum.pressure / count,
+            }
+        })
+    }
+
+    /// Temperature in Fahrenheit.
+    pub fn temperature(&self) -> f64 {
+        self.temperature
+    }
+
+    pub fn temperature_in(&self, unit: TemperatureUnit) -> f64 {
+        unit.convert(self.temperature)
+    }
+
+    /// Relative humidity in percent.
+    pub fn humidity(&self) -> f64 {
+        self.humidity
+    }
+
+    /// Pressure in inches of mercury.
+    pub fn pressure(&self) -> f64 {
+        self.pressure
+    }
+
+    pub fn pressure_in(&self, unit: PressureUnit) -> f64 {
+        unit.convert(self.pressure)
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct MeasurementsBuilder {
+    temperature: Option<f64>,
+    humidity: Option<f64>,
+    pressure: Option<f64>,
+}
+
+impl MeasurementsBuilder {
+    pub fn temperature(mut self, value: f64, unit: TemperatureUnit) -> Self {
+        self.temperature = Some(unit.normalize(value));
+        self
+    }
+
+    pub fn humidity(mut self, value: f64) -> Self {
+        self.humidity = Some(value);
+        self
+    }
+
+    pub fn pressure(mut self, value: f64, unit: PressureUnit) -> Self {
+        self.pressure = Some(unit.normalize(value));
+        self
+    }
+
+    pub fn build(self) -> Result<Measurements, MeasurementsError> {
+        let temperature = self
+            .temperature
+            .ok_or(MeasurementsError::MissingTemperature)?;
+        let humidity = self.humidity.ok_or(MeasurementsError::MissingHumidity)?;
+        let pressure = self.pressure.ok_or(MeasurementsError::MissingPressure)?;
+        if !TEMPERATURE_RANGE.contains(&temperature) {
+            return Err(MeasurementsError::TemperatureOutOfRange(temperature));
+        }
+        if !HUMIDITY_RANGE.contains(&humidity) {
+            return Err(MeasurementsError::HumidityOutOfRange(humidity));
+        }
+        if !PRESSURE_RANGE.contains(&pressure) {
+            return Err(MeasurementsError::PressureOutOfRange(pressure));
+        }
+        Ok(Measurements {
+            temperature,
+            humidity,
+            pressure,
+        })
+    }
+}
+
+/// Builder preset to 72.3F, 50.1% humidity and 29.92 inHg, for tests.
+#[cfg(test)]
+pub(crate) fn fixture_builder() -> MeasurementsBuilder {
+    Measurements::builder()
+        .temperature(72.3, TemperatureUnit::Fahrenheit)
+        .humidity(50.1)
+        .pressure(29.92, PressureUnit::InchesOfMercury)
+}
+
+/// `temperature` in Fahrenheit and `humidity` at 29.92 inHg, for tests.
+#[cfg(test)]
+pub(crate) fn fixture(temperature: f64, humidity: f64) -> Measurements {
+    fixture_builder()
+        .temperature(temperature, TemperatureUnit::Fahrenheit)
+        .humidity(humidity)
+        .build()
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build() {
+        let measurements = fixture_builder().build().unwrap();
+        assert_eq!(measurements.temperature(), 72.3);
+        assert_eq!(measurements.humidity(), 50.1);
+        assert_eq!(measurements.pressure(), 29.92);
+    }
+
+    #[test]
+    fn build_missing_fields() {
+        assert_eq!(
+            Measurements::builder().build(),
+            Err(MeasurementsError::MissingTemperature)
+        );
+        assert_eq!(
+            Measurements::builder()
+                .temperature(72.3, TemperatureUnit::Fahrenheit)
+                .build(),
+            Err(MeasurementsError::MissingHumidity)
+        );
+        assert_eq!(
+            Measurements::builder()
+                .temperature(72.3, TemperatureUnit::Fahrenheit)
+                .humidity(50.1)
+                .build(),
+            Err(MeasurementsError::MissingPressure)
+        );
+    }
+
+    #[test]
+    fn build_humidity_out_of_range() {
+        assert_eq!(
+            fixture_builder().humidity(100.5).build(),
+            Err(MeasurementsError::HumidityOutOfRange(100.5))
+        );
+        assert_eq!(
+            fixture_builder().humidity(-1.0).build(),
+            Err(MeasurementsError::HumidityOutOfRange(-1.0))
+        );
+        assert!(fixture_builder().humidity(0.0).build().is_ok());
+        assert!(fixture_builder().humidity(100.0).build().is_ok());
+    }
+
+    #[test]
+    fn build_temperature_out_of_range() {
+        assert_eq!(
+            fixture_builder()
+                .temperature(150.0, TemperatureUnit::Fahrenheit)
+                .build(),
+            Err(MeasurementsError::TemperatureOutOfRange(150.0))
+        );
+        assert_eq!(
+            fixture_builder()
+                .temperature(-100.0, TemperatureUnit::Celsius)
+                .build(),
+            Err(MeasurementsError::TemperatureOutOfRange(-148.0))
+        );
+    }
+
+    #[test]
+    fn build_pressure_out_of_range() {
+        assert_eq!(
+            fixture_builder()
+                .pressure(40.0, PressureUnit::InchesOfMercury)
+                .build(),
+            Err(MeasurementsError::PressureOutOfRange(40.0))
+        );
+        assert!(fixture_builder()
+            .pressure(500.0, PressureUnit::Hectopascals)
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn build_rejects_nan() {
+        assert!(fixture_builder().humidity(f64::NAN).build().is_err());
+        assert!(fixture_builder()
+            .temperature(f64::NAN, TemperatureUnit::Celsius)
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn temperature_units() {
+        let measurements = fixture_builder()
+            .temperature(20.0, TemperatureUnit::Celsius)
+            .build()
+            .unwrap();
+        assert_eq!(measurements.temperature(), 68.0);
+        assert_eq!(
+            measurements.temperature_in(TemperatureUnit::Fahrenheit),
+            68.0
+        );
+        assert_eq!(measurements.temperature_in(TemperatureUnit::Celsius), 20.0);
+    }
+
+    #[test]
+    fn pressure_units() {
+        let measurements = fixture_builder()
+            .pressure(1013.25, PressureUnit::Hectopascals)
+            .build()
+            .unwrap();
+        assert!((measurements.pressure() - 29.92).abs() < 0.01);
+        assert!((measurements.pressure_in(PressureUnit::Hectopascals) - 1013.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn average() {
+        let measurements = [
+            fixture_builder().humidity(40.0).build().unwrap(),
+            fixture_builder().humidity(60.0).build().unwrap(),
+        ];
+        let average = Measurements::average(&measurements).unwrap();
+        assert_eq!(average.temperature(), 72.3);
+        assert_eq!(average.humidity(), 50.0);
+        assert_eq!(Measurements::average(&[]), None);
+    }
+
+    #[test]
+    fn eq() {
+        assert_eq!(fixture_builder().build(), fixture_builder().build());
+        assert_ne!(
+            fixture_builder().build(),
+            fixture_builder().humidity(60.0).build()
+        );
+    }
+}
@@ -0,0 +1,458 @@
+use crate::log::Logs;
+use crate::observer::{LoggingElement, Observer};
+use crate::publisher::Publisher;
+use crate::subject::{NotifyError, ObserverId, Registrar, Subject, WeatherData};
+use crate::Measurements;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, VecDeque};
+use std::rc::{Rc, Weak};
+
+/// A station's measurements together with the network-wide average at that time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkReading {
+    station: String,
+    measurements: Measurements,
+    average: Measurements,
+    station_count: usize,
+}
+
+impl NetworkReading {
+    pub fn station(&self) -> &str {
+        &self.station
+    }
+
+    pub fn measurements(&self) -> &Measurements {
+        &self.measurements
+    }
+
+    /// Average of the latest measurements of every station heard from so far.
+    pub fn average(&self) -> &Measurements {
+        &self.average
+    }
+
+    pub fn station_count(&self) -> usize {
+        self.station_count
+    }
+}
+
+/// Subject that observes several `WeatherData` stations and re-publishes their
+/// readings, tagged by station, to `Observer<NetworkReading>`s.
+///
+/// Readings from connected stations are delivered while the network is only
+/// shared-borrowed, so observers may read it from `update`. A station reading
+/// that arrives while the network is borrowed is queued and delivered once
+/// the current delivery, or the next reading or `record`, completes.
+#[derive(Default)]
+pub struct StationNetwork {
+    latest: BTreeMap<String, Measurements>,
+    observers: RefCell<Publisher<NetworkReading>>,
+    /// Shared with connected stations, which queue readings here when the
+    /// network itself cannot be borrowed.
+    queued: Rc<RefCell<VecDeque<(String, Measurements)>>>,
+}
+
+impl StationNetwork {
+    pub fn new() -> Rc<RefCell<StationNetwork>> {
+        Rc::new(RefCell::new(StationNetwork::default()))
+    }
+
+    /// Subscribes `network` to `station` under `name`. The subscription only
+    /// holds a weak reference to the network and removes itself from the
+    /// station once the network is dropped; remove it earlier with the
+    /// returned id.
+    pub fn connect(
+        network: &Rc<RefCell<StationNetwork>>,
+        name: &str,
+        station: &mut WeatherData,
+    ) -> ObserverId {
+        let observer = Rc::new(RefCell::new(StationObserver {
+            name: String::from(name),
+            network: Rc::downgrade(network),
+            queued: Rc::clone(&network.borrow().queued),
+            registrar: station.registrar(),
+            id: None,
+        }));
+        let id = station.register_observer(observer.clone());
+        observer.borrow_mut().id = Some(id);
+        id
+    }
+
+    pub fn latest(&self, station: &str) -> Option<&Measurements> {
+        self.latest.get(station)
+    }
+
+    pub fn average(&self) -> Option<Measurements> {
+        Measurements::average(self.latest.values())
+    }
+
+    /// Stores and publishes a reading from `station`, then any readings that
+    /// stations queued meanwhile. Returns the first error encountered.
+    pub fn record(&mut self, station: &str, measurements: Measurements) -> Result<(), NotifyError> {
+        let reading = self.store(station, measurements);
+        let mut result = self.observers.get_mut().publish(reading);
+        while let Some((station, measurements)) = self.next_queued() {
+            let reading = self.store(&station, measurements);
+            result = result.and(self.observers.get_mut().publish(reading));
+        }
+        result
+    }
+
+    fn next_queued(&self) -> Option<(String, Measurements)> {
+        self.queued.borrow_mut().pop_front()
+    }
+
+    /// Delivers queued readings until the queue is empty. Readings queued
+    /// during a delivery are picked up by the loop; if the network is already
+    /// borrowed, whoever holds it delivers them instead.
+    fn deliver_queued(network: &Rc<RefCell<StationNetwork>>) {
+        loop {
+            let Ok(mut borrowed) = network.try_borrow_mut() else {
+                return;
+            };
+            let Some((station, measurements)) = borrowed.next_queued() else {
+                return;
+            };
+            let reading = borrowed.store(&station, measurements);
+            drop(borrowed);
+            // `Observer::update` cannot report failures; downstream observers
+            // that are borrowed simply miss this reading, as they would on a
+            // station.
+            let network = network.borrow();
+            if let Ok(mut observers) = network.observers.try_borrow_mut() {
+                let _ = observers.publish(reading);
+            };
+        }
+    }
+
+    fn store(&mut self, station: &str, measurements: Measurements) -> NetworkReading {
+        self.latest
+            .insert(String::from(station), measurements.clone());
+        NetworkReading {
+            station: String::from(station),
+            measurements,
+            average: self.average().expect("a station was just recorded"),
+            station_count: self.latest.len(),
+        }
+    }
+}
+
+impl Subject<NetworkReading> for StationNetwork {
+    fn register_observer(
+        &mut self,
+        observer: Rc<RefCell<dyn Observer<NetworkReading>>>,
+    ) -> ObserverId {
+        self.observers.get_mut().register_observer(observer)
+    }
+
+    fn register_weak_observer(
+        &mut self,
+        observer: Weak<RefCell<dyn Observer<NetworkReading>>>,
+    ) -> ObserverId {
+        self.observers.get_mut().register_weak_observer(observer)
+    }
+
+    fn set_priority(&mut self, id: ObserverId, priority: i32) -> bool {
+        self.observers.get_mut().set_priority(id, priority)
+    }
+
+    fn remove_observer(&mut self, observer: &Rc<RefCell<dyn Observer<NetworkReading>>>) -> bool {
+        self.observers.get_mut().remove_observer(observer)
+    }
+
+    fn unsubscribe(&mut self, id: ObserverId) -> bool {
+        self.observers.get_mut().unsubscribe(id)
+    }
+
+    fn notify_observers(&mut self) -> Result<(), NotifyError> {
+        self.observers.get_mut().notify_observers()
+    }
+
+    /// Panics if called from a network observer's `update`.
+    fn observer_count(&self) -> usize {
+        self.observers.borrow().observer_count()
+    }
+}
+
+struct StationObserver {
+    name: String,
+    network: Weak<RefCell<StationNetwork>>,
+    queued: Rc<RefCell<VecDeque<(String, Measurements)>>>,
+    registrar: Registrar,
+    id: Option<ObserverId>,
+}
+
+impl Observer for StationObserver {
+    fn update(&mut self, metrics: &Measurements) {
+        let Some(network) = self.network.upgrade() else {
+            if let Some(id) = self.id.take() {
+                self.registrar.unsubscribe(id);
+            }
+            return;
+        };
+        self.queued
+            .borrow_mut()
+            .push_back((self.name.clone(), metrics.clone()));
+        StationNetwork::deliver_queued(&network);
+    }
+}
+
+pub struct NetworkDisplay {
+    reading: Option<NetworkReading>,
+    logs: Logs,
+}
+
+impl Default for NetworkDisplay {
+    fn default() -> Self {
+        NetworkDisplay {
+            reading: None,
+            logs: Logs::new("network"),
+        }
+    }
+}
+
+impl Observer<NetworkReading> for NetworkDisplay {
+    fn update(&mut self, reading: &NetworkReading) {
+        self.reading = Some(reading.clone());
+        self.log_data();
+    }
+}
+
+impl LoggingElement for NetworkDisplay {
+    fn log_data(&mut self) {
+        if let Some(reading) = &self.reading {
+            let summary = format!(
+                "{}: {}F degrees and {}% humidity; network average of {} stations: {:.1}F degrees and {:.1}% humidity",
+                reading.station,
+                reading.measurements.temperature(),
+                reading.measurements.humidity(),
+                reading.station_count,
+                reading.average.temperature(),
+                reading.average.humidity(),
+            );
+            self.logs.record(
+                summary,
+                vec![
+                    ("station", reading.station.as_str().into()),
+                    ("temperature", reading.measurements.temperature().into()),
+                    ("humidity", reading.measurements.humidity().into()),
+                    ("station_count", (reading.station_count as f64).into()),
+                    ("average_temperature", reading.average.temperature().into()),
+                    ("average_humidity", reading.average.humidity().into()),
+                ],
+            );
+        }
+    }
+
+    fn get_logs(&self) -> &Logs {
+        &self.logs
+    }
+
+    fn get_logs_mut(&mut self) -> &mut Logs {
+        &mut self.logs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log::TextFormatter;
+    use crate::measurements::fixture;
+
+    #[test]
+    fn aggregates_stations() {
+        let network = StationNetwork::new();
+        let display = Rc::new(RefCell::new(NetworkDisplay::default()));
+        network.borrow_mut().register_observer(display.clone());
+        let mut north = WeatherData::default();
+        let mut south = WeatherData::default();
+        StationNetwork::connect(&network, "north", &mut north);
+        StationNetwork::connect(&network, "south", &mut south);
+        north.set_measurements(fixture(60.0, 40.0)).unwrap();
+        south.set_measurements(fixture(80.0, 60.0)).unwrap();
+        north.set_measurements(fixture(62.0, 40.0)).unwrap();
+        assert_eq!(
+            display.borrow().get_logs().format(&TextFormatter),
+            vec![
+                "north: 60F degrees and 40% humidity; network average of 1 stations: 60.0F degrees and 40.0% humidity",
+                "south: 80F degrees and 60% humidity; network average of 2 stations: 70.0F degrees and 50.0% humidity",
+                "north: 62F degrees and 40% humidity; network average of 2 stations: 71.0F degrees and 50.0% humidity",
+            ]
+        );
+        assert_eq!(network.borrow().latest("south"), Some(&fixture(80.0, 60.0)));
+        assert_eq!(network.borrow().latest("east"), None);
+    }
+
+    #[test]
+    fn network_reading() {
+        let network = StationNetwork::new();
+        let readings: Rc<RefCell<Vec<NetworkReading>>> = Rc::default();
+
+        struct Recorder(Rc<RefCell<Vec<NetworkReading>>>);
+
+        impl Observer<NetworkReading> for Recorder {
+            fn update(&mut self, reading: &NetworkReading) {
+                self.0.borrow_mut().push(reading.clone());
+            }
+        }
+
+        network
+            .borrow_mut()
+            .register_observer(Rc::new(RefCell::new(Recorder(readings.clone()))));
+        let mut station = WeatherData::default();
+        StationNetwork::connect(&network, "west", &mut station);
+        station.set_measurements(fixture(70.0, 50.0)).unwrap();
+        let readings = readings.borrow();
+        assert_eq!(readings[0].station(), "west");
+        assert_eq!(readings[0].measurements(), &fixture(70.0, 50.0));
+        assert_eq!(readings[0].average(), &fixture(70.0, 50.0));
+        assert_eq!(readings[0].station_count(), 1);
+    }
+
+    #[test]
+    fn disconnect_station() {
+        let network = StationNetwork::new();
+        let display = Rc::new(RefCell::new(NetworkDisplay::default()));
+        network.borrow_mut().register_observer(display.clone());
+        let mut station = WeatherData::default();
+        let id = StationNetwork::connect(&network, "north", &mut station);
+        assert!(station.unsubscribe(id));
+        station.set_measurements(fixture(60.0, 40.0)).unwrap();
+        assert!(display.borrow().get_logs().is_empty());
+    }
+
+    #[test]
+    fn dropped_network() {
+        let network = StationNetwork::new();
+        let mut station = WeatherData::default();
+        StationNetwork::connect(&network, "north", &mut station);
+        drop(network);
+        station.set_measurements(fixture(60.0, 40.0)).unwrap();
+        assert_eq!(station.observer_count(), 0);
+    }
+
+    #[test]
+    fn observer_reads_network() {
+        struct Dashboard {
+            network: Weak<RefCell<StationNetwork>>,
+            south: Vec<Option<Measurements>>,
+        }
+
+        impl Observer<NetworkReading> for Dashboard {
+            fn update(&mut self, _reading: &NetworkReading) {
+                let network = self.network.upgrade().unwrap();
+                self.south.push(network.borrow().latest("south").cloned());
+            }
+        }
+
+        let network = StationNetwork::new();
+        let dashboard = Rc::new(RefCell::new(Dashboard {
+            network: Rc::downgrade(&network),
+            south: Vec::new(),
+        }));
+        network.borrow_mut().register_observer(dashboard.clone());
+        let mut north = WeatherData::default();
+        let mut south = WeatherData::default();
+        StationNetwork::connect(&network, "north", &mut north);
+        StationNetwork::connect(&network, "south", &mut south);
+        north.set_measurements(fixture(60.0, 40.0)).unwrap();
+        south.set_measurements(fixture(80.0, 60.0)).unwrap();
+        assert_eq!(
+            dashboard.borrow().south,
+            vec![None, Some(fixture(80.0, 60.0))]
+        );
+    }
+
+    #[test]
+    fn reading_during_delivery_is_queued() {
+        struct Relay {
+            station: Rc<RefCell<WeatherData>>,
+            stations: Vec<String>,
+        }
+
+        impl Observer<NetworkReading> for Relay {
+            fn update(&mut self, reading: &NetworkReading) {
+                self.stations.push(String::from(reading.station()));
+                if reading.station() == "north" {
+                    let _ = self
+                        .station
+                        .borrow_mut()
+                        .set_measurements(fixture(80.0, 60.0));
+                }
+            }
+        }
+
+        let network = StationNetwork::new();
+        let south = Rc::new(RefCell::new(WeatherData::default()));
+        let relay = Rc::new(RefCell::new(Relay {
+            station: south.clone(),
+            stations: Vec::new(),
+        }));
+        network.borrow_mut().register_observer(relay.clone());
+        let mut north = WeatherData::default();
+        StationNetwork::connect(&network, "north", &mut north);
+        StationNetwork::connect(&network, "south", &mut south.borrow_mut());
+        north.set_measurements(fixture(60.0, 40.0)).unwrap();
+        assert_eq!(network.borrow().latest("south"), Some(&fixture(80.0, 60.0)));
+        assert_eq!(relay.borrow().stations, vec!["north", "south"]);
+    }
+
+    #[test]
+    fn reading_while_borrowed_is_delivered_with_the_next() {
+        let network = StationNetwork::new();
+        let display = Rc::new(RefCell::new(NetworkDisplay::default()));
+        network.borrow_mut().register_observer(display.clone());
+        let mut north = WeatherData::default();
+        StationNetwork::connect(&network, "north", &mut north);
+        {
+            let _borrowed = network.borrow();
+            north.set_measurements(fixture(60.0, 40.0)).unwrap();
+        }
+        assert!(display.borrow().get_logs().is_empty());
+        network
+            .borrow_mut()
+            .record("south", fixture(80.0, 60.0))
+            .unwrap();
+        assert_eq!(display.borrow().get_logs().len(), 2);
+        assert_eq!(network.borrow().latest("north"), Some(&fixture(60.0, 40.0)));
+    }
+
+    #[test]
+    fn priorities_and_weak_observers() {
+        let network = StationNetwork::new();
+        let first = Rc::new(RefCell::new(NetworkDisplay::default()));
+        let second = Rc::new(RefCell::new(NetworkDisplay::default()));
+        let weak: Rc<RefCell<dyn Observer<NetworkReading>>> =
+            Rc::new(RefCell::new(NetworkDisplay::default()));
+        let observer: Rc<RefCell<dyn Observer<NetworkReading>>> = first.clone();
+        network.borrow_mut().register_observer(observer.clone());
+        network
+            .borrow_mut()
+            .register_prioritized_observer(second.clone(), 1);
+        network
+            .borrow_mut()
+            .register_weak_observer(Rc::downgrade(&weak));
+        assert_eq!(network.borrow().observer_count(), 3);
+        drop(weak);
+        assert_eq!(network.borrow().observer_count(), 2);
+        assert!(network.borrow_mut().remove_observer(&observer));
+        network
+            .borrow_mut()
+            .record("north", fixture(60.0, 40.0))
+            .unwrap();
+        assert!(first.borrow().get_logs().is_empty());
+        assert_eq!(second.borrow().get_logs().len(), 1);
+    }
+
+    #[test]
+    fn unsubscribe() {
+        let network = StationNetwork::new();
+        let display = Rc::new(RefCell::new(NetworkDisplay::default()));
+        let id = network.borrow_mut().register_observer(display.clone());
+        assert!(network.borrow_mut().unsubscribe(id));
+        network
+            .borrow_mut()
+            .record("north", fixture(60.0, 40.0))
+            .unwrap();
+        assert!(display.borrow().get_logs().is_empty());
+    }
+}
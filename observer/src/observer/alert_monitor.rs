@@ -0,0 +1,318 @@
+use super::{LoggingElement, Observer};
+use crate::log::Logs;
+use crate::Measurements;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Alert {
+    Frost,
+    Heat,
+    HighHumidity,
+}
+
+impl Alert {
+    pub const ALL: [Alert; 3] = [Alert::Frost, Alert::Heat, Alert::HighHumidity];
+
+    fn name(self) -> &'static str {
+        match self {
+            Alert::Frost => "Frost warning",
+            Alert::Heat => "Heat warning",
+            Alert::HighHumidity => "High humidity",
+        }
+    }
+
+    fn id(self) -> &'static str {
+        match self {
+            Alert::Frost => "frost",
+            Alert::Heat => "heat",
+            Alert::HighHumidity => "high_humidity",
+        }
+    }
+
+    fn value(self, measurements: &Measurements) -> f64 {
+        match self {
+            Alert::Frost | Alert::Heat => measurements.temperature(),
+            Alert::HighHumidity => measurements.humidity(),
+        }
+    }
+
+    fn unit(self) -> &'static str {
+        match self {
+            Alert::Frost | Alert::Heat => "F",
+            Alert::HighHumidity => "%",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AlertEvent {
+    Raised { alert: Alert, value: f64 },
+    Cleared { alert: Alert, value: f64 },
+}
+
+/// An alert is raised once a value reaches `level` and only cleared once it has
+/// moved back past `level` by more than `hysteresis`, so readings hovering
+/// around the boundary do not make it flap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Threshold {
+    level: f64,
+    hysteresis: f64,
+}
+
+impl Threshold {
+    pub fn new(level: f64, hysteresis: f64) -> Threshold {
+        Threshold { level, hysteresis }
+    }
+
+    pub fn level(&self) -> f64 {
+        self.level
+    }
+
+    pub fn hysteresis(&self) -> f64 {
+        self.hysteresis
+    }
+}
+
+/// Frost fires at or below its level; heat and high humidity at or above theirs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AlertConfig {
+    pub frost: Threshold,
+    pub heat: Threshold,
+    pub high_humidity: Threshold,
+}
+
+impl Default for AlertConfig {
+    fn default() -> Self {
+        AlertConfig {
+            frost: Threshold::new(32.0, 2.0),
+            heat: Threshold::new(95.0, 2.0),
+            high_humidity: Threshold::new(85.0, 5.0),
+        }
+    }
+}
+
+impl AlertConfig {
+    fn threshold(&self, alert: Alert) -> Threshold {
+        match alert {
+            Alert::Frost => self.frost,
+            Alert::Heat => self.heat,
+            Alert::HighHumidity => self.high_humidity,
+        }
+    }
+}
+
+type Listener = Box<dyn FnMut(&AlertEvent)>;
+
+/// Observer raising and clearing weather alerts as measurements cross the
+/// configured thresholds.
+pub struct AlertMonitor {
+    config: AlertConfig,
+    active: Vec<Alert>,
+    history: Vec<AlertEvent>,
+    unlogged: usize,
+    listener: Option<Listener>,
+    logs: Logs,
+}
+
+impl Default for AlertMonitor {
+    fn default() -> Self {
+        AlertMonitor::new(AlertConfig::default())
+    }
+}
+
+impl AlertMonitor {
+    pub fn new(config: AlertConfig) -> AlertMonitor {
+        AlertMonitor {
+            config,
+            active: Vec::new(),
+            history: Vec::new(),
+            unlogged: 0,
+            listener: None,
+            logs: Logs::new("alerts"),
+        }
+    }
+
+    /// Calls `listener` with every event as soon as it is raised or cleared.
+    pub fn on_event(&mut self, listener: impl FnMut(&AlertEvent) + 'static) {
+        self.listener = Some(Box::new(listener));
+    }
+
+    pub fn is_active(&self, alert: Alert) -> bool {
+        self.active.contains(&alert)
+    }
+
+    pub fn active(&self) -> &[Alert] {
+        &self.active
+    }
+
+    /// Every alert raised or cleared so far, oldest first.
+    pub fn history(&self) -> &[AlertEvent] {
+        &self.history
+    }
+
+    fn check(&self, alert: Alert, value: f64) -> Option<AlertEvent> {
+        let Threshold { level, hysteresis } = self.config.threshold(alert);
+        let (reached, cleared) = match alert {
+            Alert::Frost => (value <= level, value > level + hysteresis),
+            Alert::Heat | Alert::HighHumidity => (value >= level, value < level - hysteresis),
+        };
+        match self.is_active(alert) {
+            false if reached => Some(AlertEvent::Raised { alert, value }),
+            true if cleared => Some(AlertEvent::Cleared { alert, value }),
+            _ => None,
+        }
+    }
+
+    fn fire(&mut self, event: AlertEvent) {
+        match event {
+            AlertEvent::Raised { alert, .. } => self.active.push(alert),
+            AlertEvent::Cleared { alert, .. } => self.active.retain(|active| *active != alert),
+        }
+        if let Some(listener) = self.listener.as_mut() {
+            listener(&event);
+        }
+        self.history.push(event);
+        self.unlogged += 1;
+    }
+}
+
+impl Observer for AlertMonitor {
+    fn update(&mut self, metrics: &Measurements) {
+        for alert in Alert::ALL {
+            if let Some(event) = self.check(alert, alert.value(metrics)) {
+                self.fire(event);
+            }
+        }
+        self.log_data();
+    }
+}
+
+impl LoggingElement for AlertMonitor {
+    /// Logs the events fired since the last call.
+    fn log_data(&mut self) {
+        let start = self.history.len() - self.unlogged;
+        for event in &self.history[start..] {
+            let (alert, value, state) = match *event {
+                AlertEvent::Raised { alert, value } => (alert, value, "raised"),
+                AlertEvent::Cleared { alert, value } => (alert, value, "cleared"),
+            };
+            let summary = format!("{} {} at {}{}", alert.name(), state, value, alert.unit());
+            self.logs.record(
+                summary,
+                vec![
+                    ("alert", alert.id().into()),
+                    ("state", state.into()),
+                    ("value", value.into()),
+                ],
+            );
+        }
+        self.unlogged = 0;
+    }
+
+    fn get_logs(&self) -> &Logs {
+        &self.logs
+    }
+
+    fn get_logs_mut(&mut self) -> &mut Logs {
+        &mut self.logs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log::TextFormatter;
+    use crate::measurements::fixture;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn frost_warning() {
+        let mut monitor = AlertMonitor::default();
+        monitor.update(&fixture(40.0, 50.0));
+        assert!(monitor.active().is_empty());
+        monitor.update(&fixture(31.0, 50.0));
+        assert!(monitor.is_active(Alert::Frost));
+        assert_eq!(
+            monitor.history(),
+            &[AlertEvent::Raised {
+                alert: Alert::Frost,
+                value: 31.0,
+            }]
+        );
+    }
+
+    #[test]
+    fn hysteresis_prevents_flapping() {
+        let mut monitor = AlertMonitor::default();
+        for temperature in [31.0, 33.0, 31.5, 34.0, 32.0, 33.5] {
+            monitor.update(&fixture(temperature, 50.0));
+        }
+        assert!(monitor.is_active(Alert::Frost));
+        assert_eq!(monitor.history().len(), 1);
+        monitor.update(&fixture(34.5, 50.0));
+        assert!(!monitor.is_active(Alert::Frost));
+        assert_eq!(
+            monitor.history()[1],
+            AlertEvent::Cleared {
+                alert: Alert::Frost,
+                value: 34.5,
+            }
+        );
+    }
+
+    #[test]
+    fn heat_and_humidity() {
+        let mut monitor = AlertMonitor::default();
+        monitor.update(&fixture(96.0, 90.0));
+        assert_eq!(monitor.active(), &[Alert::Heat, Alert::HighHumidity]);
+        monitor.update(&fixture(94.0, 81.0));
+        assert_eq!(monitor.active(), &[Alert::Heat, Alert::HighHumidity]);
+        monitor.update(&fixture(92.0, 79.0));
+        assert!(monitor.active().is_empty());
+        assert_eq!(monitor.history().len(), 4);
+    }
+
+    #[test]
+    fn custom_config() {
+        let mut monitor = AlertMonitor::new(AlertConfig {
+            heat: Threshold::new(80.0, 0.0),
+            ..AlertConfig::default()
+        });
+        monitor.update(&fixture(80.0, 50.0));
+        assert!(monitor.is_active(Alert::Heat));
+        monitor.update(&fixture(79.9, 50.0));
+        assert!(!monitor.is_active(Alert::Heat));
+    }
+
+    #[test]
+    fn on_event() {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let mut monitor = AlertMonitor::default();
+        {
+            let events = Rc::clone(&events);
+            monitor.on_event(move |event| events.borrow_mut().push(*event));
+        }
+        monitor.update(&fixture(30.0, 50.0));
+        monitor.update(&fixture(40.0, 50.0));
+        assert_eq!(*events.borrow(), monitor.history());
+    }
+
+    #[test]
+    fn log_data() {
+        let mut monitor = AlertMonitor::default();
+        monitor.update(&fixture(30.0, 50.0));
+        monitor.update(&fixture(31.0, 50.0));
+        monitor.update(&fixture(40.0, 90.0));
+        assert_eq!(
+            monitor.get_logs().format(&TextFormatter),
+            vec![
+                "Frost warning raised at 30F",
+                "Frost warning cleared at 40F",
+                "High humidity raised at 90%",
+            ]
+        );
+        let entry = monitor.get_logs().last().unwrap();
+        assert_eq!(entry.display(), "alerts");
+        assert_eq!(entry.number("value"), Some(90.0));
+    }
+}
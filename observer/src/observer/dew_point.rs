@@ -0,0 +1,88 @@
+use super::metric_display::{Metric, MetricDisplay};
+use crate::measurements::TemperatureUnit;
+use crate::Measurements;
+
+pub type DewPointDisplay = MetricDisplay<DewPoint>;
+
+// Magnus formula coefficients recommended by Alduchov and Eskridge (1996).
+const MAGNUS_B: f64 = 17.625;
+const MAGNUS_C: f64 = 243.04;
+
+#[derive(Default)]
+pub struct DewPoint;
+
+/// Dew point in Celsius from a temperature in Celsius and relative humidity in
+/// percent, or `None` for completely dry air.
+pub fn dew_point(temperature: f64, humidity: f64) -> Option<f64> {
+    if humidity <= 0.0 {
+        return None;
+    }
+    let gamma = (humidity / 100.0).ln() + MAGNUS_B * temperature / (MAGNUS_C + temperature);
+    Some(MAGNUS_C * gamma / (MAGNUS_B - gamma))
+}
+
+impl Metric for DewPoint {
+    fn id(&self) -> &'static str {
+        "dew_point"
+    }
+
+    fn name(&self) -> &'static str {
+        "Dew point"
+    }
+
+    fn compute(&self, measurements: &Measurements) -> Option<f64> {
+        let temperature = measurements.temperature_in(TemperatureUnit::Celsius);
+        dew_point(temperature, measurements.humidity()).map(|value| value * 9.0 / 5.0 + 32.0)
+    }
+
+    fn describe(&self, value: f64) -> String {
+        format!(
+            "Dew point is {:.1}F ({:.1}C)",
+            value,
+            (value - 32.0) * 5.0 / 9.0
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log::TextFormatter;
+    use crate::measurements::fixture;
+    use crate::observer::{LoggingElement, Observer};
+
+    // Reference values from the NOAA dew point calculator.
+    #[test]
+    fn dew_point_reference_values() {
+        for (temperature, humidity, expected) in
+            [(20.0, 50.0, 9.3), (30.0, 70.0, 23.9), (25.0, 60.0, 16.7)]
+        {
+            let point = dew_point(temperature, humidity).unwrap();
+            assert!(
+                (point - expected).abs() <= 0.1,
+                "dew_point({temperature}, {humidity}) = {point}, expected {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn dew_point_saturated() {
+        assert!((dew_point(10.0, 100.0).unwrap() - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn dew_point_dry_air() {
+        assert_eq!(dew_point(10.0, 0.0), None);
+    }
+
+    #[test]
+    fn update() {
+        let mut display = DewPointDisplay::default();
+        display.update(&fixture(68.0, 50.0));
+        display.update(&fixture(68.0, 0.0));
+        assert_eq!(
+            display.get_logs().format(&TextFormatter),
+            vec!["Dew point is 48.7F (9.3C)", "Dew point is unavailable"]
+        );
+    }
+}
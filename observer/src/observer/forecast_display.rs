@@ -0,0 +1,151 @@
+use super::{LoggingElement, Observer};
+use crate::log::Logs;
+use crate::Measurements;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Forecast {
+    Improving,
+    CoolerRainy,
+    Same,
+}
+
+/// Predicts the weather from the trend between the last two pressure readings.
+pub struct ForecastDisplay {
+    current_pressure: Option<f64>,
+    last_pressure: Option<f64>,
+    logs: Logs,
+}
+
+impl Default for ForecastDisplay {
+    fn default() -> Self {
+        ForecastDisplay {
+            current_pressure: None,
+            last_pressure: None,
+            logs: Logs::new("forecast"),
+        }
+    }
+}
+
+impl ForecastDisplay {
+    pub fn forecast(&self) -> Option<Forecast> {
+        let current = self.current_pressure?;
+        let last = self.last_pressure.unwrap_or(current);
+        let forecast = if current > last {
+            Forecast::Improving
+        } else if current < last {
+            Forecast::CoolerRainy
+        } else {
+            Forecast::Same
+        };
+        Some(forecast)
+    }
+}
+
+impl Observer for ForecastDisplay {
+    fn update(&mut self, metrics: &Measurements) {
+        self.last_pressure = self.current_pressure;
+        self.current_pressure = Some(metrics.pressure());
+        self.log_data();
+    }
+}
+
+impl LoggingElement for ForecastDisplay {
+    /// Logs nothing until the first reading.
+    fn log_data(&mut self) {
+        let (Some(forecast), Some(pressure)) = (self.forecast(), self.current_pressure) else {
+            return;
+        };
+        let (summary, forecast) = match forecast {
+            Forecast::Improving => ("Forecast: Improving weather on the way!", "improving"),
+            Forecast::CoolerRainy => (
+                "Forecast: Watch out for cooler, rainy weather",
+                "cooler_rainy",
+            ),
+            Forecast::Same => ("Forecast: More of the same", "same"),
+        };
+        self.logs.record(
+            String::from(summary),
+            vec![("forecast", forecast.into()), ("pressure", pressure.into())],
+        );
+    }
+
+    fn get_logs(&self) -> &Logs {
+        &self.logs
+    }
+
+    fn get_logs_mut(&mut self) -> &mut Logs {
+        &mut self.logs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log::{TextFormatter, Value};
+    use crate::measurements::{fixture_builder, PressureUnit};
+
+    fn measurements(pressure: f64) -> Measurements {
+        fixture_builder()
+            .pressure(pressure, PressureUnit::InchesOfMercury)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn no_readings() {
+        let mut display = ForecastDisplay::default();
+        assert_eq!(display.forecast(), None);
+        display.log_data();
+        assert!(display.get_logs().is_empty());
+    }
+
+    #[test]
+    fn first_reading() {
+        let mut display = ForecastDisplay::default();
+        display.update(&measurements(29.92));
+        assert_eq!(display.forecast(), Some(Forecast::Same));
+    }
+
+    #[test]
+    fn rising_pressure() {
+        let mut display = ForecastDisplay::default();
+        display.update(&measurements(29.2));
+        display.update(&measurements(29.92));
+        assert_eq!(display.forecast(), Some(Forecast::Improving));
+    }
+
+    #[test]
+    fn falling_pressure() {
+        let mut display = ForecastDisplay::default();
+        display.update(&measurements(29.92));
+        display.update(&measurements(29.2));
+        assert_eq!(display.forecast(), Some(Forecast::CoolerRainy));
+    }
+
+    #[test]
+    fn log_data() {
+        let mut display = ForecastDisplay::default();
+        display.update(&measurements(29.92));
+        display.update(&measurements(30.4));
+        display.update(&measurements(29.2));
+        assert_eq!(
+            display.get_logs().format(&TextFormatter),
+            vec![
+                "Forecast: More of the same",
+                "Forecast: Improving weather on the way!",
+                "Forecast: Watch out for cooler, rainy weather",
+            ]
+        );
+    }
+
+    #[test]
+    fn structured_log() {
+        let mut display = ForecastDisplay::default();
+        display.update(&measurements(29.92));
+        display.update(&measurements(29.2));
+        let entry = display.get_logs().last().unwrap();
+        assert_eq!(entry.display(), "forecast");
+        assert_eq!(entry.field("forecast"), Some(&Value::from("cooler_rainy")));
+        assert_eq!(entry.number("pressure"), Some(29.2));
+    }
+}
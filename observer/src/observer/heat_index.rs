@@ -0,0 +1,104 @@
+use super::metric_display::{Metric, MetricDisplay};
+use crate::Measurements;
+
+pub type HeatIndexDisplay = MetricDisplay<HeatIndex>;
+
+#[derive(Default)]
+pub struct HeatIndex;
+
+/// Heat index in Fahrenheit, following the National Weather Service algorithm:
+/// Steadman's simple formula, refined by the Rothfusz regression and its
+/// low/high humidity adjustments once the result reaches 80F.
+pub fn heat_index(temperature: f64, humidity: f64) -> f64 {
+    let simple = 0.5 * (temperature + 61.0 + (temperature - 68.0) * 1.2 + humidity * 0.094);
+    if (simple + temperature) / 2.0 < 80.0 {
+        return simple;
+    }
+    let (t, rh) = (temperature, humidity);
+    let mut index = -42.379 + 2.04901523 * t + 10.14333127 * rh
+        - 0.22475541 * t * rh
+        - 0.00683783 * t * t
+        - 0.05481717 * rh * rh
+        + 0.00122874 * t * t * rh
+        + 0.00085282 * t * rh * rh
+        - 0.00000199 * t * t * rh * rh;
+    if rh < 13.0 && (80.0..=112.0).contains(&t) {
+        index -= (13.0 - rh) / 4.0 * ((17.0 - (t - 95.0).abs()) / 17.0).sqrt();
+    } else if rh > 85.0 && (80.0..=87.0).contains(&t) {
+        index += (rh - 85.0) / 10.0 * ((87.0 - t) / 5.0);
+    }
+    index
+}
+
+impl Metric for HeatIndex {
+    fn id(&self) -> &'static str {
+        "heat_index"
+    }
+
+    fn name(&self) -> &'static str {
+        "Heat index"
+    }
+
+    fn compute(&self, measurements: &Measurements) -> Option<f64> {
+        Some(heat_index(
+            measurements.temperature(),
+            measurements.humidity(),
+        ))
+    }
+
+    fn describe(&self, value: f64) -> String {
+        format!("Heat index is {:.1}F", value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log::TextFormatter;
+    use crate::measurements::fixture;
+    use crate::observer::{LoggingElement, Observer};
+
+    // Reference values from the National Weather Service heat index chart.
+    #[test]
+    fn heat_index_reference_values() {
+        for (temperature, humidity, expected) in [
+            (80.0, 40.0, 80.0),
+            (90.0, 70.0, 106.0),
+            (96.0, 65.0, 121.0),
+            (100.0, 40.0, 109.0),
+            (85.0, 90.0, 102.0),
+        ] {
+            let index = heat_index(temperature, humidity);
+            assert!(
+                (index - expected).abs() <= 0.5,
+                "heat_index({temperature}, {humidity}) = {index}, expected {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn heat_index_below_80() {
+        assert_eq!(heat_index(70.0, 50.0), 69.05);
+    }
+
+    #[test]
+    fn update() {
+        let mut display = HeatIndexDisplay::default();
+        assert_eq!(display.value(), None);
+        display.update(&fixture(90.0, 70.0));
+        assert_eq!(display.value().map(f64::round), Some(106.0));
+        assert_eq!(
+            display.get_logs().format(&TextFormatter),
+            vec!["Heat index is 105.9F"]
+        );
+    }
+
+    #[test]
+    fn structured_log() {
+        let mut display = HeatIndexDisplay::default();
+        display.update(&fixture(90.0, 70.0));
+        let entry = display.get_logs().last().unwrap();
+        assert_eq!(entry.display(), "heat_index");
+        assert_eq!(entry.number("value"), display.value());
+    }
+}
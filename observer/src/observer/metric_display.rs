@@ -0,0 +1,71 @@
+use super::{LoggingElement, Observer};
+use crate::log::Logs;
+use crate::Measurements;
+
+/// A value derived from a single set of measurements, e.g. the heat index.
+pub trait Metric {
+    /// Identifies the metric's display in structured logs, e.g. `heat_index`.
+    fn id(&self) -> &'static str;
+
+    fn name(&self) -> &'static str;
+
+    /// Returns `None` when the metric is undefined for `measurements`.
+    fn compute(&self, measurements: &Measurements) -> Option<f64>;
+
+    fn describe(&self, value: f64) -> String;
+}
+
+/// Observer that computes a `Metric` on every update and logs its description.
+pub struct MetricDisplay<M: Metric> {
+    metric: M,
+    value: Option<f64>,
+    logs: Logs,
+}
+
+impl<M: Metric + Default> Default for MetricDisplay<M> {
+    fn default() -> Self {
+        MetricDisplay::new(M::default())
+    }
+}
+
+impl<M: Metric> MetricDisplay<M> {
+    pub fn new(metric: M) -> MetricDisplay<M> {
+        MetricDisplay {
+            logs: Logs::new(metric.id()),
+            metric,
+            value: None,
+        }
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        self.value
+    }
+}
+
+impl<M: Metric> Observer for MetricDisplay<M> {
+    fn update(&mut self, metrics: &Measurements) {
+        self.value = self.metric.compute(metrics);
+        self.log_data();
+    }
+}
+
+impl<M: Metric> LoggingElement for MetricDisplay<M> {
+    fn log_data(&mut self) {
+        match self.value {
+            Some(value) => self
+                .logs
+                .record(self.metric.describe(value), vec![("value", value.into())]),
+            None => self
+                .logs
+                .record(format!("{} is unavailable", self.metric.name()), Vec::new()),
+        }
+    }
+
+    fn get_logs(&self) -> &Logs {
+        &self.logs
+    }
+
+    fn get_logs_mut(&mut self) -> &mut Logs {
+        &mut self.logs
+    }
+}
@@ -0,0 +1,91 @@
+use crate::log::Logs;
+use crate::subject::WeatherView;
+use crate::Measurements;
+
+mod alert_monitor;
+mod dew_point;
+mod forecast_display;
+mod heat_index;
+mod metric_display;
+mod statistics_display;
+
+pub use alert_monitor::{Alert, AlertConfig, AlertEvent, AlertMonitor, Threshold};
+pub use dew_point::{dew_point, DewPoint, DewPointDisplay};
+pub use forecast_display::{Forecast, ForecastDisplay};
+pub use heat_index::{heat_index, HeatIndex, HeatIndexDisplay};
+pub use metric_display::{Metric, MetricDisplay};
+pub use statistics_display::{Statistics, StatisticsDisplay};
+
+/// Receives events of type `E` from a `Subject`.
+pub trait Observer<E: ?Sized = Measurements> {
+    fn update(&mut self, event: &E);
+}
+
+/// Observer that is handed a view of the subject and queries the fields it needs.
+pub trait PullObserver {
+    fn update(&mut self, subject: &dyn WeatherView);
+}
+
+pub trait LoggingElement {
+    fn log_data(&mut self);
+    fn get_logs(&self) -> &Logs;
+    fn get_logs_mut(&mut self) -> &mut Logs;
+}
+
+pub struct CurrentConditionsDisplay {
+    temperature: f64,
+    humidity: f64,
+    logs: Logs,
+}
+
+impl Default for CurrentConditionsDisplay {
+    fn default() -> Self {
+        CurrentConditionsDisplay {
+            temperature: 0.0,
+            humidity: 0.0,
+            logs: Logs::new("current_conditions"),
+        }
+    }
+}
+
+impl Observer for CurrentConditionsDisplay {
+    fn update(&mut self, metrics: &Measurements) {
+        self.temperature = metrics.temperature();
+        self.humidity = metrics.humidity();
+        self.log_data();
+    }
+}
+
+impl PullObserver for CurrentConditionsDisplay {
+    fn update(&mut self, subject: &dyn WeatherView) {
+        if let (Some(temperature), Some(humidity)) = (subject.temperature(), subject.humidity()) {
+            self.temperature = temperature;
+            self.humidity = humidity;
+            self.log_data();
+        }
+    }
+}
+
+impl LoggingElement for CurrentConditionsDisplay {
+    fn log_data(&mut self) {
+        let summary = format!(
+            "Current conditions: {}F degrees and {}% humidity",
+            self.temperature, self.humidity,
+        );
+        self.logs.record(
+            summary,
+            vec![
+                ("temperature", self.temperature.into()),
+                ("humidity", self.humidity.into()),
+            ],
+        );
+    }
+
+    fn get_logs(&self) -> &Logs {
+        &self.logs
+    }
+
+    fn get_logs_mut(&mut self) -> &mut Logs {
+        &mut self.logs
+    }
+}
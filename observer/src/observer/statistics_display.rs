@@ -0,0 +1,187 @@
+use super::{LoggingElement, Observer};
+use crate::log::Logs;
+use crate::Measurements;
+
+/// Running minimum, maximum and average of a series of readings.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Statistics {
+    min: f64,
+    max: f64,
+    sum: f64,
+    count: u32,
+}
+
+impl Statistics {
+    fn push(&mut self, value: f64) {
+        if self.count == 0 {
+            self.min = value;
+            self.max = value;
+        } else {
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.min)
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.max)
+    }
+
+    pub fn average(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum / f64::from(self.count))
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Average, maximum and minimum, or `None` before the first reading.
+    fn summary(&self) -> Option<[f64; 3]> {
+        Some([self.average()?, self.max()?, self.min()?])
+    }
+}
+
+pub struct StatisticsDisplay {
+    temperature: Statistics,
+    humidity: Statistics,
+    logs: Logs,
+}
+
+impl Default for StatisticsDisplay {
+    fn default() -> Self {
+        StatisticsDisplay {
+            temperature: Statistics::default(),
+            humidity: Statistics::default(),
+            logs: Logs::new("statistics"),
+        }
+    }
+}
+
+impl StatisticsDisplay {
+    pub fn temperature(&self) -> &Statistics {
+        &self.temperature
+    }
+
+    pub fn humidity(&self) -> &Statistics {
+        &self.humidity
+    }
+}
+
+impl Observer for StatisticsDisplay {
+    fn update(&mut self, metrics: &Measurements) {
+        self.temperature.push(metrics.temperature());
+        self.humidity.push(metrics.humidity());
+        self.log_data();
+    }
+}
+
+impl LoggingElement for StatisticsDisplay {
+    /// Logs nothing until the first reading.
+    fn log_data(&mut self) {
+        let (
+            Some([temperature_average, temperature_max, temperature_min]),
+            Some([humidity_average, humidity_max, humidity_min]),
+        ) = (self.temperature.summary(), self.humidity.summary())
+        else {
+            return;
+        };
+        let summary = format!(
+            "Avg/Max/Min temperature = {:.1}/{:.1}/{:.1}F and humidity = {:.1}/{:.1}/{:.1}%",
+            temperature_average,
+            temperature_max,
+            temperature_min,
+            humidity_average,
+            humidity_max,
+            humidity_min,
+        );
+        self.logs.record(
+            summary,
+            vec![
+                ("temperature_average", temperature_average.into()),
+                ("temperature_max", temperature_max.into()),
+                ("temperature_min", temperature_min.into()),
+                ("humidity_average", humidity_average.into()),
+                ("humidity_max", humidity_max.into()),
+                ("humidity_min", humidity_min.into()),
+            ],
+        );
+    }
+
+    fn get_logs(&self) -> &Logs {
+        &self.logs
+    }
+
+    fn get_logs_mut(&mut self) -> &mut Logs {
+        &mut self.logs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log::TextFormatter;
+    use crate::measurements::fixture;
+
+    #[test]
+    fn empty() {
+        let display = StatisticsDisplay::default();
+        assert_eq!(display.temperature().min(), None);
+        assert_eq!(display.temperature().max(), None);
+        assert_eq!(display.temperature().average(), None);
+        assert_eq!(display.humidity().count(), 0);
+        assert!(display.get_logs().is_empty());
+    }
+
+    #[test]
+    fn update() {
+        let mut display = StatisticsDisplay::default();
+        display.update(&fixture(80.0, 65.0));
+        display.update(&fixture(82.0, 70.0));
+        display.update(&fixture(78.0, 90.0));
+        assert_eq!(display.temperature().min(), Some(78.0));
+        assert_eq!(display.temperature().max(), Some(82.0));
+        assert_eq!(display.temperature().average(), Some(80.0));
+        assert_eq!(display.humidity().min(), Some(65.0));
+        assert_eq!(display.humidity().max(), Some(90.0));
+        assert_eq!(display.humidity().average(), Some(75.0));
+        assert_eq!(display.humidity().count(), 3);
+    }
+
+    #[test]
+    fn log_data() {
+        let mut display = StatisticsDisplay::default();
+        display.update(&fixture(80.0, 65.0));
+        display.update(&fixture(82.0, 70.0));
+        assert_eq!(
+            display.get_logs().format(&TextFormatter),
+            vec![
+                "Avg/Max/Min temperature = 80.0/80.0/80.0F and humidity = 65.0/65.0/65.0%",
+                "Avg/Max/Min temperature = 81.0/82.0/80.0F and humidity = 67.5/70.0/65.0%",
+            ]
+        );
+    }
+
+    #[test]
+    fn log_data_before_readings() {
+        let mut display = StatisticsDisplay::default();
+        display.log_data();
+        assert!(display.get_logs().is_empty());
+    }
+
+    #[test]
+    fn structured_log() {
+        let mut display = StatisticsDisplay::default();
+        display.update(&fixture(80.0, 65.0));
+        display.update(&fixture(82.0, 70.0));
+        let entry = display.get_logs().last().unwrap();
+        assert_eq!(entry.display(), "statistics");
+        assert_eq!(entry.number("temperature_average"), Some(81.0));
+        assert_eq!(entry.number("temperature_max"), Some(82.0));
+        assert_eq!(entry.number("humidity_min"), Some(65.0));
+    }
+}
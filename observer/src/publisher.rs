@@ -0,0 +1,385 @@
+use crate::observer::Observer;
+use crate::registry::{Entry, Handle, Pending, Registry};
+use crate::subject::{NotifyError, ObserverId, Subject};
+use crate::Measurements;
+use std::cell::{BorrowMutError, RefCell};
+use std::rc::{Rc, Weak};
+
+type Adapter<E> = Rc<dyn Fn(&E) -> Result<(), BorrowMutError>>;
+
+type Predicate<E> = Box<dyn FnMut(&E) -> bool>;
+
+enum Listener<E> {
+    Observer(Handle<dyn Observer<E>>),
+    /// Delivers through a closure, for observers of another shape such as
+    /// `PullObserver`s.
+    Adapter(Adapter<E>),
+}
+
+pub(crate) enum Target<E> {
+    Observer(Rc<RefCell<dyn Observer<E>>>),
+    Adapter(Adapter<E>),
+}
+
+impl<E> Listener<E> {
+    fn upgrade(&self) -> Option<Target<E>> {
+        match self {
+            Listener::Observer(handle) => handle.upgrade().map(Target::Observer),
+            Listener::Adapter(adapter) => Some(Target::Adapter(Rc::clone(adapter))),
+        }
+    }
+}
+
+pub(crate) struct Subscription<E> {
+    listener: Listener<E>,
+    filter: Option<Predicate<E>>,
+}
+
+impl<E> Subscription<E> {
+    fn new(listener: Listener<E>) -> Subscription<E> {
+        Subscription {
+            listener,
+            filter: None,
+        }
+    }
+}
+
+impl<E> Entry for Subscription<E> {
+    type Event = E;
+    type Target = Target<E>;
+
+    fn is_alive(&self) -> bool {
+        match &self.listener {
+            Listener::Observer(handle) => handle.is_alive(),
+            Listener::Adapter(_) => true,
+        }
+    }
+
+    fn target(&mut self, event: &E) -> Option<Target<E>> {
+        let matches = self.filter.as_mut().is_none_or(|filter| filter(event));
+        if matches {
+            self.listener.upgrade()
+        } else {
+            None
+        }
+    }
+
+    fn deliver(target: &Target<E>, event: &E) -> Result<(), BorrowMutError> {
+        match target {
+            Target::Observer(observer) => observer
+                .try_borrow_mut()
+                .map(|mut observer| observer.update(event)),
+            Target::Adapter(adapter) => adapter(event),
+        }
+    }
+}
+
+/// `Subject` for events of any type, such as stock quotes or build results.
+/// Keeps the latest event so that `notify_observers` can resend it.
+pub struct Publisher<E> {
+    observers: Registry<Subscription<E>>,
+    latest: Option<E>,
+}
+
+impl<E> Default for Publisher<E> {
+    fn default() -> Self {
+        Publisher {
+            observers: Registry::default(),
+            latest: None,
+        }
+    }
+}
+
+impl<E> Publisher<E> {
+    pub fn latest(&self) -> Option<&E> {
+        self.latest.as_ref()
+    }
+
+    /// Stores `event` as the latest event and notifies every observer of it.
+    pub fn publish(&mut self, event: E) -> Result<(), NotifyError> {
+        self.latest = Some(event);
+        self.notify_observers()
+    }
+
+    /// Stores `event` as the latest event without notifying anyone.
+    pub(crate) fn set_latest(&mut self, event: E) {
+        self.latest = Some(event);
+    }
+
+    /// Handle for observers that need to register or remove observers from
+    /// inside `update`.
+    pub fn registrar(&self) -> Registrar<E> {
+        Registrar {
+            pending: self.observers.pending(),
+        }
+    }
+
+    /// Only notifies the observer registered as `id` of events that `filter`
+    /// accepts, replacing any previous filter.
+    pub fn set_filter(&mut self, id: ObserverId, filter: impl FnMut(&E) -> bool + 'static) -> bool {
+        match self.observers.get_mut(id) {
+            Some(subscription) => {
+                subscription.filter = Some(Box::new(filter));
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn register_adapter(
+        &mut self,
+        adapter: impl Fn(&E) -> Result<(), BorrowMutError> + 'static,
+    ) -> ObserverId {
+        self.observers
+            .push(Subscription::new(Listener::Adapter(Rc::new(adapter))))
+    }
+
+    pub(crate) fn is_registered(&mut self, id: ObserverId) -> bool {
+        self.observers
+            .get(id)
+            .is_some_and(|subscription| subscription.is_alive())
+    }
+
+    /// Sends `event` to the observer registered as `id` alone, bypassing its filter.
+    pub(crate) fn notify_one(&mut self, id: ObserverId, event: &E) -> Result<(), NotifyError> {
+        let target = self
+            .observers
+            .get(id)
+            .and_then(|subscription| subscription.listener.upgrade())
+            .ok_or(NotifyError::UnknownObserver(id))?;
+        Subscription::deliver(&target, event).map_err(|_| NotifyError::ObserversBorrowed(vec![id]))
+    }
+}
+
+impl<E> Subject<E> for Publisher<E> {
+    fn register_observer(&mut self, observer: Rc<RefCell<dyn Observer<E>>>) -> ObserverId {
+        self.observers
+            .push(Subscription::new(Listener::Observer(Handle::Strong(
+                observer,
+            ))))
+    }
+
+    fn register_weak_observer(&mut self, observer: Weak<RefCell<dyn Observer<E>>>) -> ObserverId {
+        self.observers
+            .push(Subscription::new(Listener::Observer(Handle::Weak(
+                observer,
+            ))))
+    }
+
+    fn set_priority(&mut self, id: ObserverId, priority: i32) -> bool {
+        self.observers.set_priority(id, priority)
+    }
+
+    fn remove_observer(&mut self, observer: &Rc<RefCell<dyn Observer<E>>>) -> bool {
+        self.observers
+            .remove_where(|_, subscription| match &subscription.listener {
+                Listener::Observer(handle) => handle.points_to(observer),
+                Listener::Adapter(_) => false,
+            })
+    }
+
+    fn unsubscribe(&mut self, id: ObserverId) -> bool {
+        self.observers.remove(id)
+    }
+
+    fn notify_observers(&mut self) -> Result<(), NotifyError> {
+        let event = self.latest.as_ref().ok_or(NotifyError::NothingPublished)?;
+        self.observers.notify(event)
+    }
+
+    fn observer_count(&self) -> usize {
+        self.observers.live_count()
+    }
+}
+
+/// Queues registrations and removals for a subject from inside
+/// `Observer::update`, where the subject itself is already borrowed. Changes
+/// queued during a notification are applied once it completes; otherwise they
+/// take effect on the subject's next call, such as `set_priority` or
+/// `observer_count`.
+pub struct Registrar<E = Measurements> {
+    pending: Rc<RefCell<Pending<Subscription<E>>>>,
+}
+
+impl<E> Clone for Registrar<E> {
+    fn clone(&self) -> Self {
+        Registrar {
+            pending: Rc::clone(&self.pending),
+        }
+    }
+}
+
+impl<E> Registrar<E> {
+    fn queue(&self, listener: Listener<E>) -> ObserverId {
+        self.pending
+            .borrow_mut()
+            .register(Subscription::new(listener))
+    }
+
+    pub fn register_observer(&self, observer: Rc<RefCell<dyn Observer<E>>>) -> ObserverId {
+        self.queue(Listener::Observer(Handle::Strong(observer)))
+    }
+
+    pub fn register_weak_observer(&self, observer: Weak<RefCell<dyn Observer<E>>>) -> ObserverId {
+        self.queue(Listener::Observer(Handle::Weak(observer)))
+    }
+
+    pub(crate) fn register_adapter(
+        &self,
+        adapter: impl Fn(&E) -> Result<(), BorrowMutError> + 'static,
+    ) -> ObserverId {
+        self.queue(Listener::Adapter(Rc::new(adapter)))
+    }
+
+    pub fn unsubscribe(&self, id: ObserverId) {
+        self.pending.borrow_mut().unsubscribe(id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Quote {
+        symbol: &'static str,
+        price: f64,
+    }
+
+    #[derive(Default)]
+    struct Ticker {
+        quotes: Vec<String>,
+    }
+
+    impl Observer<Quote> for Ticker {
+        fn update(&mut self, quote: &Quote) {
+            self.quotes
+                .push(format!("{} {:.2}", quote.symbol, quote.price));
+        }
+    }
+
+    struct BuildFinished {
+        passed: bool,
+    }
+
+    #[derive(Default)]
+    struct BuildBadge {
+        failures: u32,
+    }
+
+    impl Observer<BuildFinished> for BuildBadge {
+        fn update(&mut self, build: &BuildFinished) {
+            if !build.passed {
+                self.failures += 1;
+            }
+        }
+    }
+
+    fn quote(symbol: &'static str, price: f64) -> Quote {
+        Quote { symbol, price }
+    }
+
+    #[test]
+    fn publish() {
+        let mut publisher = Publisher::default();
+        let ticker = Rc::new(RefCell::new(Ticker::default()));
+        publisher.register_observer(ticker.clone());
+        publisher.publish(quote("ACME", 12.5)).unwrap();
+        publisher.publish(quote("INIT", 3.25)).unwrap();
+        assert_eq!(ticker.borrow().quotes, vec!["ACME 12.50", "INIT 3.25"]);
+        assert_eq!(publisher.latest(), Some(&quote("INIT", 3.25)));
+    }
+
+    #[test]
+    fn nothing_published() {
+        let mut publisher: Publisher<Quote> = Publisher::default();
+        assert_eq!(
+            publisher.notify_observers(),
+            Err(NotifyError::NothingPublished)
+        );
+    }
+
+    #[test]
+    fn other_event_types() {
+        let mut publisher = Publisher::default();
+        let badge = Rc::new(RefCell::new(BuildBadge::default()));
+        publisher.register_observer(badge.clone());
+        publisher.publish(BuildFinished { passed: false }).unwrap();
+        publisher.publish(BuildFinished { passed: true }).unwrap();
+        publisher.notify_observers().unwrap();
+        assert_eq!(badge.borrow().failures, 1);
+    }
+
+    #[test]
+    fn priority_and_removal() {
+        let mut publisher = Publisher::default();
+        let first = Rc::new(RefCell::new(Ticker::default()));
+        let second: Rc<RefCell<dyn Observer<Quote>>> = Rc::new(RefCell::new(Ticker::default()));
+        let weak = Rc::new(RefCell::new(Ticker::default()));
+        let observer: Weak<RefCell<dyn Observer<Quote>>> = Rc::downgrade(&weak) as _;
+        publisher.register_observer(second.clone());
+        let id = publisher.register_prioritized_observer(first.clone(), 1);
+        publisher.register_weak_observer(observer);
+        assert_eq!(publisher.observer_count(), 3);
+        drop(weak);
+        assert_eq!(publisher.observer_count(), 2);
+        assert!(publisher.remove_observer(&second));
+        assert!(publisher.unsubscribe(id));
+        assert_eq!(publisher.observer_count(), 0);
+        publisher.publish(quote("ACME", 12.5)).unwrap();
+        assert!(first.borrow().quotes.is_empty());
+    }
+
+    #[test]
+    fn borrowed_observer_is_skipped() {
+        let mut publisher = Publisher::default();
+        let ticker = Rc::new(RefCell::new(Ticker::default()));
+        let id = publisher.register_observer(ticker.clone());
+        let _guard = ticker.borrow_mut();
+        assert_eq!(
+            publisher.publish(quote("ACME", 12.5)),
+            Err(NotifyError::ObserversBorrowed(vec![id]))
+        );
+    }
+
+    #[test]
+    fn filter() {
+        let mut publisher = Publisher::default();
+        let ticker = Rc::new(RefCell::new(Ticker::default()));
+        let id = publisher.register_observer(ticker.clone());
+        assert!(publisher.set_filter(id, |quote: &Quote| quote.symbol == "ACME"));
+        publisher.publish(quote("INIT", 3.25)).unwrap();
+        publisher.publish(quote("ACME", 12.5)).unwrap();
+        assert_eq!(ticker.borrow().quotes, vec!["ACME 12.50"]);
+    }
+
+    #[test]
+    fn observer_unsubscribes_itself() {
+        struct Once {
+            registrar: Registrar<Quote>,
+            id: Option<ObserverId>,
+            updates: u32,
+        }
+
+        impl Observer<Quote> for Once {
+            fn update(&mut self, _quote: &Quote) {
+                self.updates += 1;
+                if let Some(id) = self.id.take() {
+                    self.registrar.unsubscribe(id);
+                }
+            }
+        }
+
+        let mut publisher = Publisher::default();
+        let once = Rc::new(RefCell::new(Once {
+            registrar: publisher.registrar(),
+            id: None,
+            updates: 0,
+        }));
+        once.borrow_mut().id = Some(publisher.register_observer(once.clone()));
+        publisher.publish(quote("ACME", 12.5)).unwrap();
+        assert_eq!(publisher.observer_count(), 0);
+        publisher.publish(quote("ACME", 12.75)).unwrap();
+        assert_eq!(once.borrow().updates, 1);
+    }
+}
@@ -0,0 +1,301 @@
+use crate::subject::{NotifyError, ObserverId};
+use std::cell::{BorrowMutError, RefCell};
+use std::cmp::Reverse;
+use std::rc::{Rc, Weak};
+
+/// What a subject stores per observer.
+pub(crate) trait Entry {
+    type Event: ?Sized;
+    /// What a notification is delivered to, kept alive for the whole cycle.
+    type Target;
+
+    /// Whether the observer can still be notified; dead entries are pruned.
+    fn is_alive(&self) -> bool;
+
+    /// The target to notify of `event`, or `None` if this entry skips it.
+    fn target(&mut self, event: &Self::Event) -> Option<Self::Target>;
+
+    fn deliver(target: &Self::Target, event: &Self::Event) -> Result<(), BorrowMutError>;
+}
+
+pub(crate) enum Handle<T: ?Sized> {
+    Strong(Rc<RefCell<T>>),
+    Weak(Weak<RefCell<T>>),
+}
+
+impl<T: ?Sized> Handle<T> {
+    pub(crate) fn upgrade(&self) -> Option<Rc<RefCell<T>>> {
+        match self {
+            Handle::Strong(observer) => Some(Rc::clone(observer)),
+            Handle::Weak(observer) => observer.upgrade(),
+        }
+    }
+
+    pub(crate) fn points_to(&self, observer: &Rc<RefCell<T>>) -> bool {
+        match self {
+            Handle::Strong(registered) => Rc::ptr_eq(registered, observer),
+            Handle::Weak(registered) => {
+                std::ptr::addr_eq(registered.as_ptr(), Rc::as_ptr(observer))
+            }
+        }
+    }
+
+    pub(crate) fn is_alive(&self) -> bool {
+        match self {
+            Handle::Strong(_) => true,
+            Handle::Weak(observer) => observer.strong_count() > 0,
+        }
+    }
+}
+
+enum Change<R> {
+    Register(ObserverId, R),
+    Unsubscribe(ObserverId),
+}
+
+/// Id allocation and changes queued while the registry cannot be borrowed.
+pub(crate) struct Pending<R> {
+    next_id: usize,
+    changes: Vec<Change<R>>,
+}
+
+impl<R> Pending<R> {
+    fn next_id(&mut self) -> ObserverId {
+        let id = ObserverId(self.next_id);
+        self.next_id += 1;
+        id
+    }
+
+    pub(crate) fn register(&mut self, entry: R) -> ObserverId {
+        let id = self.next_id();
+        self.changes.push(Change::Register(id, entry));
+        id
+    }
+
+    pub(crate) fn unsubscribe(&mut self, id: ObserverId) {
+        self.changes.push(Change::Unsubscribe(id));
+    }
+}
+
+struct Slot<T> {
+    id: ObserverId,
+    priority: i32,
+    /// Position in registration order. Differs from `id` for registrations
+    /// queued through `pending`, which count as registered once applied.
+    sequence: usize,
+    entry: T,
+}
+
+/// Entries kept in notification order: descending priority, then
+/// registration order.
+pub(crate) struct Slots<T> {
+    slots: Vec<Slot<T>>,
+    next_sequence: usize,
+}
+
+impl<T> Default for Slots<T> {
+    fn default() -> Self {
+        Slots {
+            slots: Vec::new(),
+            next_sequence: 0,
+        }
+    }
+}
+
+impl<T> Slots<T> {
+    pub(crate) fn insert(&mut self, id: ObserverId, entry: T) {
+        // A new entry has the default priority, so it goes after every other
+        // entry of priority 0 or more.
+        let index = self.slots.partition_point(|slot| slot.priority >= 0);
+        self.slots.insert(
+            index,
+            Slot {
+                id,
+                priority: 0,
+                sequence: self.next_sequence,
+                entry,
+            },
+        );
+        self.next_sequence += 1;
+    }
+
+    pub(crate) fn get(&self, id: ObserverId) -> Option<&T> {
+        self.slots
+            .iter()
+            .find(|slot| slot.id == id)
+            .map(|slot| &slot.entry)
+    }
+
+    pub(crate) fn get_mut(&mut self, id: ObserverId) -> Option<&mut T> {
+        self.slots
+            .iter_mut()
+            .find(|slot| slot.id == id)
+            .map(|slot| &mut slot.entry)
+    }
+
+    pub(crate) fn set_priority(&mut self, id: ObserverId, priority: i32) -> bool {
+        match self.slots.iter_mut().find(|slot| slot.id == id) {
+            Some(slot) => {
+                slot.priority = priority;
+                self.slots
+                    .sort_by_key(|slot| (Reverse(slot.priority), slot.sequence));
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn remove_where(&mut self, mut f: impl FnMut(ObserverId, &T) -> bool) -> bool {
+        let len = self.slots.len();
+        self.slots.retain(|slot| !f(slot.id, &slot.entry));
+        self.slots.len() != len
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (ObserverId, &T)> {
+        self.slots.iter().map(|slot| (slot.id, &slot.entry))
+    }
+
+    pub(crate) fn iter_mut(&mut self) -> impl Iterator<Item = (ObserverId, &mut T)> {
+        self.slots.iter_mut().map(|slot| (slot.id, &mut slot.entry))
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.slots.len()
+    }
+}
+
+/// Registration machinery shared by every subject, independent of the event
+/// type.
+pub(crate) struct Registry<R> {
+    slots: Slots<R>,
+    pending: Rc<RefCell<Pending<R>>>,
+}
+
+impl<R> Default for Registry<R> {
+    fn default() -> Self {
+        Registry {
+            slots: Slots::default(),
+            pending: Rc::new(RefCell::new(Pending {
+                next_id: 0,
+                changes: Vec::new(),
+            })),
+        }
+    }
+}
+
+impl<R: Entry> Registry<R> {
+    /// Queue shared with registrars; see `apply_pending`.
+    pub(crate) fn pending(&self) -> Rc<RefCell<Pending<R>>> {
+        Rc::clone(&self.pending)
+    }
+
+    pub(crate) fn push(&mut self, entry: R) -> ObserverId {
+        self.apply_pending();
+        let id = self.pending.borrow_mut().next_id();
+        self.slots.insert(id, entry);
+        id
+    }
+
+    pub(crate) fn get(&mut self, id: ObserverId) -> Option<&R> {
+        self.apply_pending();
+        self.slots.get(id)
+    }
+
+    pub(crate) fn get_mut(&mut self, id: ObserverId) -> Option<&mut R> {
+        self.apply_pending();
+        self.slots.get_mut(id)
+    }
+
+    pub(crate) fn set_priority(&mut self, id: ObserverId, priority: i32) -> bool {
+        self.apply_pending();
+        self.slots.set_priority(id, priority)
+    }
+
+    pub(crate) fn remove(&mut self, id: ObserverId) -> bool {
+        self.remove_where(|registered, _| registered == id)
+    }
+
+    pub(crate) fn remove_where(&mut self, f: impl FnMut(ObserverId, &R) -> bool) -> bool {
+        self.apply_pending();
+        self.slots.remove_where(f)
+    }
+
+    /// Applies the changes queued through `pending`, in the order they were
+    /// made. Every method that reads or changes the registry does this first,
+    /// so only changes queued during a notification wait for it to complete.
+    pub(crate) fn apply_pending(&mut self) {
+        let changes = std::mem::take(&mut self.pending.borrow_mut().changes);
+        for change in changes {
+            match change {
+                Change::Register(id, entry) => self.slots.insert(id, entry),
+                Change::Unsubscribe(id) => {
+                    self.slots.remove_where(|registered, _| registered == id);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn prune(&mut self) {
+        self.slots.remove_where(|_, entry| !entry.is_alive());
+    }
+
+    /// Delivers `event` to every live entry in notification order. The entries
+    /// notified are fixed up front; changes queued through `pending` during the
+    /// cycle are applied once it completes.
+    pub(crate) fn notify(&mut self, event: &R::Event) -> Result<(), NotifyError> {
+        self.apply_pending();
+        self.prune();
+        let targets: Vec<_> = self
+            .slots
+            .iter_mut()
+            .filter_map(|(id, entry)| Some((id, entry.target(event)?)))
+            .collect();
+        let mut borrowed = Vec::new();
+        for (id, target) in targets {
+            if R::deliver(&target, event).is_err() {
+                borrowed.push(id);
+            }
+        }
+        self.apply_pending();
+        if borrowed.is_empty() {
+            Ok(())
+        } else {
+            Err(NotifyError::ObserversBorrowed(borrowed))
+        }
+    }
+
+    /// Counts changes still queued in `pending` as applied.
+    pub(crate) fn live_count(&self) -> usize {
+        let mut live: Vec<ObserverId> = self
+            .slots
+            .iter()
+            .filter(|(_, entry)| entry.is_alive())
+            .map(|(id, _)| id)
+            .collect();
+        for change in &self.pending.borrow().changes {
+            match change {
+                Change::Register(id, entry) if entry.is_alive() => live.push(*id),
+                Change::Register(..) => {}
+                Change::Unsubscribe(id) => live.retain(|live| live != id),
+            }
+        }
+        live.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ties_keep_insertion_order_not_id_order() {
+        let mut slots = Slots::default();
+        slots.insert(ObserverId(1), "first");
+        slots.insert(ObserverId(0), "second");
+        slots.insert(ObserverId(2), "third");
+        assert!(slots.set_priority(ObserverId(2), 1));
+        assert!(slots.set_priority(ObserverId(2), 0));
+        let order: Vec<_> = slots.iter().map(|(_, entry)| *entry).collect();
+        assert_eq!(order, vec!["first", "second", "third"]);
+    }
+}
@@ -0,0 +1,112 @@
+use std::time::{Duration, SystemTime};
+
+/// When a subject notifies its observers after its measurements change.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationMode {
+    /// Notify on every change.
+    #[default]
+    Immediate,
+    /// Notify at most once per interval; a change inside the interval is
+    /// delivered once the interval has passed.
+    Throttle(Duration),
+    /// Notify once the measurements have stopped changing for the quiet period.
+    Debounce(Duration),
+}
+
+/// Tracks whether a change is waiting to be delivered under a `NotificationMode`.
+#[derive(Debug, Default)]
+pub(crate) struct Schedule {
+    mode: NotificationMode,
+    pending: bool,
+    last_changed: Option<SystemTime>,
+    last_notified: Option<SystemTime>,
+}
+
+fn elapsed(since: Option<SystemTime>, now: SystemTime, period: Duration) -> bool {
+    since.is_none_or(|since| now.duration_since(since).unwrap_or_default() >= period)
+}
+
+impl Schedule {
+    pub(crate) fn mode(&self) -> NotificationMode {
+        self.mode
+    }
+
+    pub(crate) fn set_mode(&mut self, mode: NotificationMode) {
+        self.mode = mode;
+    }
+
+    pub(crate) fn changed(&mut self, now: SystemTime) {
+        self.pending = true;
+        self.last_changed = Some(now);
+    }
+
+    pub(crate) fn notified(&mut self, now: SystemTime) {
+        self.pending = false;
+        self.last_notified = Some(now);
+    }
+
+    pub(crate) fn is_pending(&self) -> bool {
+        self.pending
+    }
+
+    /// Whether a pending change should be delivered at `now`.
+    pub(crate) fn is_due(&self, now: SystemTime) -> bool {
+        self.pending
+            && match self.mode {
+                NotificationMode::Immediate => true,
+                NotificationMode::Throttle(interval) => elapsed(self.last_notified, now, interval),
+                NotificationMode::Debounce(quiet) => elapsed(self.last_changed, now, quiet),
+            }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(millis: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn immediate() {
+        let mut schedule = Schedule::default();
+        assert!(!schedule.is_due(at(0)));
+        schedule.changed(at(0));
+        assert!(schedule.is_due(at(0)));
+        schedule.notified(at(0));
+        assert!(!schedule.is_due(at(0)));
+    }
+
+    #[test]
+    fn throttle() {
+        let mut schedule = Schedule::default();
+        schedule.set_mode(NotificationMode::Throttle(Duration::from_millis(100)));
+        schedule.changed(at(0));
+        assert!(schedule.is_due(at(0)));
+        schedule.notified(at(0));
+        schedule.changed(at(50));
+        assert!(!schedule.is_due(at(50)));
+        assert!(!schedule.is_due(at(99)));
+        assert!(schedule.is_due(at(100)));
+    }
+
+    #[test]
+    fn debounce() {
+        let mut schedule = Schedule::default();
+        schedule.set_mode(NotificationMode::Debounce(Duration::from_millis(100)));
+        schedule.changed(at(0));
+        assert!(!schedule.is_due(at(50)));
+        schedule.changed(at(50));
+        assert!(!schedule.is_due(at(100)));
+        assert!(schedule.is_due(at(150)));
+    }
+
+    #[test]
+    fn clock_moving_backwards() {
+        let mut schedule = Schedule::default();
+        schedule.set_mode(NotificationMode::Debounce(Duration::from_millis(100)));
+        schedule.changed(at(500));
+        assert!(!schedule.is_due(at(0)));
+    }
+}
@@ -0,0 +1,355 @@
+use crate::observer::Observer;
+use crate::registry::Slots;
+use crate::subject::{NotifyError, ObserverId};
+use crate::Measurements;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+pub type SharedObserver = Arc<Mutex<dyn Observer + Send>>;
+
+/// `Subject` counterpart whose observers may live on, and be notified from, any thread.
+pub trait SharedSubject: Send + Sync {
+    fn register_observer(&self, observer: SharedObserver) -> ObserverId;
+
+    fn register_prioritized_observer(&self, observer: SharedObserver, priority: i32) -> ObserverId {
+        let id = self.register_observer(observer);
+        self.set_priority(id, priority);
+        id
+    }
+
+    /// Observers are notified in descending priority, then in registration
+    /// order. Every observer starts with priority 0.
+    fn set_priority(&self, id: ObserverId, priority: i32) -> bool;
+
+    /// Removes every registration of `observer`, compared by identity.
+    fn remove_observer(&self, observer: &SharedObserver) -> bool;
+
+    fn unsubscribe(&self, id: ObserverId) -> bool;
+
+    fn notify_observers(&self) -> Result<(), NotifyError>;
+
+    fn observer_count(&self) -> usize;
+}
+
+#[derive(Default)]
+struct State {
+    observers: Slots<SharedObserver>,
+    next_id: usize,
+    measurements: Option<Measurements>,
+}
+
+impl State {
+    fn snapshot(&self) -> Vec<(ObserverId, SharedObserver)> {
+        self.observers
+            .iter()
+            .map(|(id, observer)| (id, Arc::clone(observer)))
+            .collect()
+    }
+}
+
+fn notify(
+    observers: Vec<(ObserverId, SharedObserver)>,
+    measurements: &Measurements,
+) -> Result<(), NotifyError> {
+    let mut poisoned = Vec::new();
+    for (id, observer) in observers {
+        match observer.lock() {
+            Ok(mut observer) => observer.update(measurements),
+            Err(_) => poisoned.push(id),
+        }
+    }
+    if poisoned.is_empty() {
+        Ok(())
+    } else {
+        Err(NotifyError::ObserversPoisoned(poisoned))
+    }
+}
+
+/// Notifications are delivered one at a time, in the order the measurements
+/// were set, so every observer ends up with the latest measurements. Observers
+/// may register or remove observers from within `update`, but must not set
+/// measurements or notify, which would deadlock.
+#[derive(Default)]
+pub struct SharedWeatherData {
+    state: Mutex<State>,
+    /// Held for a whole notification cycle to serialise deliveries.
+    notifying: Mutex<()>,
+}
+
+impl SharedWeatherData {
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+
+    fn notifying(&self) -> MutexGuard<'_, ()> {
+        // Only observers panic while this is held, and they are reported as
+        // poisoned themselves.
+        self.notifying
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn measurements(&self) -> Option<Measurements> {
+        self.state().measurements.clone()
+    }
+
+    pub fn set_measurements(&self, measurements: Measurements) -> Result<(), NotifyError> {
+        let _notifying = self.notifying();
+        let observers = {
+            let mut state = self.state();
+            state.measurements = Some(measurements.clone());
+            state.snapshot()
+        };
+        // Observers are notified without holding the state lock so that they
+        // may register or remove observers from within `update`.
+        notify(observers, &measurements)
+    }
+}
+
+impl SharedSubject for SharedWeatherData {
+    fn register_observer(&self, observer: SharedObserver) -> ObserverId {
+        let mut state = self.state();
+        let id = ObserverId(state.next_id);
+        state.next_id += 1;
+        state.observers.insert(id, observer);
+        id
+    }
+
+    fn set_priority(&self, id: ObserverId, priority: i32) -> bool {
+        self.state().observers.set_priority(id, priority)
+    }
+
+    fn remove_observer(&self, observer: &SharedObserver) -> bool {
+        self.state()
+            .observers
+            .remove_where(|_, registered| Arc::ptr_eq(registered, observer))
+    }
+
+    fn unsubscribe(&self, id: ObserverId) -> bool {
+        self.state()
+            .observers
+            .remove_where(|registered, _| registered == id)
+    }
+
+    fn notify_observers(&self) -> Result<(), NotifyError> {
+        let _notifying = self.notifying();
+        let (measurements, observers) = {
+            let state = self.state();
+            (state.measurements.clone(), state.snapshot())
+        };
+        let measurements = measurements.ok_or(NotifyError::NothingPublished)?;
+        notify(observers, &measurements)
+    }
+
+    fn observer_count(&self) -> usize {
+        self.state().observers.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log::TextFormatter;
+    use crate::measurements::fixture;
+    use crate::observer::{CurrentConditionsDisplay, LoggingElement};
+    use std::thread;
+
+    #[test]
+    fn update_observer() {
+        let weather_data = SharedWeatherData::default();
+        let current_display = Arc::new(Mutex::new(CurrentConditionsDisplay::default()));
+        weather_data.register_observer(current_display.clone());
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        assert_eq!(
+            current_display
+                .lock()
+                .unwrap()
+                .get_logs()
+                .format(&TextFormatter),
+            vec!["Current conditions: 72.3F degrees and 50.1% humidity"]
+        );
+    }
+
+    #[test]
+    fn remove_observer() {
+        let weather_data = SharedWeatherData::default();
+        let current_display = Arc::new(Mutex::new(CurrentConditionsDisplay::default()));
+        let observer: SharedObserver = current_display.clone();
+        weather_data.register_observer(Arc::clone(&observer));
+        assert!(weather_data.remove_observer(&observer));
+        assert_eq!(weather_data.observer_count(), 0);
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        assert!(current_display.lock().unwrap().get_logs().is_empty());
+    }
+
+    #[test]
+    fn unsubscribe() {
+        let weather_data = SharedWeatherData::default();
+        let current_display = Arc::new(Mutex::new(CurrentConditionsDisplay::default()));
+        let id = weather_data.register_observer(current_display.clone());
+        assert!(weather_data.unsubscribe(id));
+        assert!(!weather_data.unsubscribe(id));
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        assert!(current_display.lock().unwrap().get_logs().is_empty());
+    }
+
+    #[test]
+    fn publish_from_several_threads() {
+        let weather_data = SharedWeatherData::default();
+        let current_display = Arc::new(Mutex::new(CurrentConditionsDisplay::default()));
+        weather_data.register_observer(current_display.clone());
+        thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..25 {
+                        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+                    }
+                });
+            }
+        });
+        assert_eq!(current_display.lock().unwrap().get_logs().len(), 100);
+    }
+
+    #[test]
+    fn observers_end_with_latest_measurements() {
+        #[derive(Default)]
+        struct Latest(Option<Measurements>);
+
+        impl Observer for Latest {
+            fn update(&mut self, metrics: &Measurements) {
+                self.0 = Some(metrics.clone());
+            }
+        }
+
+        let weather_data = SharedWeatherData::default();
+        let first = Arc::new(Mutex::new(Latest::default()));
+        let second = Arc::new(Mutex::new(Latest::default()));
+        weather_data.register_observer(first.clone());
+        weather_data.register_observer(second.clone());
+        thread::scope(|scope| {
+            for publisher in 0..4 {
+                let weather_data = &weather_data;
+                scope.spawn(move || {
+                    for i in 0..25 {
+                        let temperature = f64::from(publisher * 25 + i);
+                        let measurements = fixture(temperature, 50.1);
+                        weather_data.set_measurements(measurements).unwrap();
+                    }
+                });
+            }
+        });
+        let latest = weather_data.measurements();
+        assert!(latest.is_some());
+        assert_eq!(first.lock().unwrap().0, latest);
+        assert_eq!(second.lock().unwrap().0, latest);
+    }
+
+    #[test]
+    fn priority() {
+        struct Named {
+            name: &'static str,
+            order: Arc<Mutex<Vec<&'static str>>>,
+        }
+
+        impl Observer for Named {
+            fn update(&mut self, _metrics: &Measurements) {
+                self.order.lock().unwrap().push(self.name);
+            }
+        }
+
+        let weather_data = SharedWeatherData::default();
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let named = |name| {
+            Arc::new(Mutex::new(Named {
+                name,
+                order: Arc::clone(&order),
+            }))
+        };
+        let low = weather_data.register_observer(named("low"));
+        weather_data.register_observer(named("default"));
+        weather_data.register_prioritized_observer(named("high"), 5);
+        weather_data.register_observer(named("late"));
+        assert!(weather_data.set_priority(low, -1));
+        assert!(!weather_data.set_priority(ObserverId(99), 1));
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        assert_eq!(
+            *order.lock().unwrap(),
+            vec!["high", "default", "late", "low"]
+        );
+    }
+
+    #[test]
+    fn observer_on_another_thread() {
+        let weather_data = Arc::new(SharedWeatherData::default());
+        let current_display = Arc::new(Mutex::new(CurrentConditionsDisplay::default()));
+        let handle = {
+            let weather_data = Arc::clone(&weather_data);
+            let current_display = Arc::clone(&current_display);
+            thread::spawn(move || weather_data.register_observer(current_display))
+        };
+        handle.join().unwrap();
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        let logs = thread::spawn(move || current_display.lock().unwrap().get_logs().len());
+        assert_eq!(logs.join().unwrap(), 1);
+    }
+
+    #[test]
+    fn notify_observers_before_measurements() {
+        let weather_data = SharedWeatherData::default();
+        assert_eq!(
+            weather_data.notify_observers(),
+            Err(NotifyError::NothingPublished)
+        );
+    }
+
+    #[test]
+    fn notify_observers_skips_poisoned_observer() {
+        let weather_data = SharedWeatherData::default();
+        let current_display_a = Arc::new(Mutex::new(CurrentConditionsDisplay::default()));
+        let current_display_b = Arc::new(Mutex::new(CurrentConditionsDisplay::default()));
+        let id = weather_data.register_observer(current_display_a.clone());
+        weather_data.register_observer(current_display_b.clone());
+        let poison = Arc::clone(&current_display_a);
+        let _ = thread::spawn(move || {
+            let _guard = poison.lock().unwrap();
+            panic!("display crashed");
+        })
+        .join();
+        assert_eq!(
+            weather_data.set_measurements(fixture(72.3, 50.1)),
+            Err(NotifyError::ObserversPoisoned(vec![id]))
+        );
+        assert_eq!(current_display_b.lock().unwrap().get_logs().len(), 1);
+    }
+
+    #[test]
+    fn observer_removed_during_notification_is_still_notified() {
+        struct Remover {
+            weather_data: Arc<SharedWeatherData>,
+            target: Option<ObserverId>,
+        }
+
+        impl Observer for Remover {
+            fn update(&mut self, _metrics: &Measurements) {
+                if let Some(id) = self.target.take() {
+                    self.weather_data.unsubscribe(id);
+                }
+            }
+        }
+
+        let weather_data = Arc::new(SharedWeatherData::default());
+        let remover = Arc::new(Mutex::new(Remover {
+            weather_data: Arc::clone(&weather_data),
+            target: None,
+        }));
+        let current_display = Arc::new(Mutex::new(CurrentConditionsDisplay::default()));
+        let remover_id = weather_data.register_observer(remover.clone());
+        let id = weather_data.register_observer(current_display.clone());
+        remover.lock().unwrap().target = Some(id);
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        assert_eq!(current_display.lock().unwrap().get_logs().len(), 1);
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        assert_eq!(current_display.lock().unwrap().get_logs().len(), 1);
+        // Breaks the cycle between the subject and the remover.
+        assert!(weather_data.unsubscribe(remover_id));
+    }
+}
@@ -0,0 +1,333 @@
+use crate::Measurements;
+use futures_core::Stream;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::{Rc, Weak};
+use std::task::{Context, Poll, Waker};
+
+/// What a subscriber's queue does when measurements arrive faster than it reads them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backpressure {
+    /// Only the most recent unread measurements are kept.
+    LatestOnly,
+    /// Up to `n` measurements are queued; `publish` waits while the queue is full.
+    Bounded(usize),
+    /// Up to `n` measurements are queued; the oldest is discarded when full.
+    DropOldest(usize),
+}
+
+struct Channel {
+    queue: VecDeque<Measurements>,
+    backpressure: Backpressure,
+    closed: bool,
+    receiver: Option<Waker>,
+    senders: Vec<Waker>,
+}
+
+impl Channel {
+    fn has_room(&self) -> bool {
+        match self.backpressure {
+            Backpressure::Bounded(capacity) => self.queue.len() < capacity,
+            Backpressure::LatestOnly | Backpressure::DropOldest(_) => true,
+        }
+    }
+
+    fn push(&mut self, measurements: Measurements) {
+        match self.backpressure {
+            Backpressure::LatestOnly => self.queue.clear(),
+            Backpressure::DropOldest(capacity) if self.queue.len() == capacity => {
+                self.queue.pop_front();
+            }
+            Backpressure::Bounded(_) | Backpressure::DropOldest(_) => {}
+        }
+        self.queue.push_back(measurements);
+        if let Some(receiver) = self.receiver.take() {
+            receiver.wake();
+        }
+    }
+}
+
+/// Subject for single-threaded async code that hands each subscriber its own
+/// `Stream` of measurements.
+#[derive(Default)]
+pub struct StreamWeatherData {
+    channels: RefCell<Vec<Weak<RefCell<Channel>>>>,
+}
+
+impl StreamWeatherData {
+    /// # Panics
+    ///
+    /// Panics if a `Bounded` or `DropOldest` capacity is zero.
+    pub fn subscribe(&self, backpressure: Backpressure) -> MeasurementStream {
+        if let Backpressure::Bounded(capacity) | Backpressure::DropOldest(capacity) = backpressure {
+            assert!(capacity > 0, "stream capacity must be greater than zero");
+        }
+        let channel = Rc::new(RefCell::new(Channel {
+            queue: VecDeque::new(),
+            backpressure,
+            closed: false,
+            receiver: None,
+            senders: Vec::new(),
+        }));
+        self.channels.borrow_mut().push(Rc::downgrade(&channel));
+        MeasurementStream { channel }
+    }
+
+    /// Resolves once `measurements` has been queued for every live subscriber.
+    pub fn publish(&self, measurements: Measurements) -> Publish<'_> {
+        Publish {
+            subject: self,
+            measurements: Some(measurements),
+        }
+    }
+
+    /// Number of streams that have not been dropped.
+    pub fn subscriber_count(&self) -> usize {
+        self.live_channels().len()
+    }
+
+    fn live_channels(&self) -> Vec<Rc<RefCell<Channel>>> {
+        let mut channels = self.channels.borrow_mut();
+        channels.retain(|channel| channel.strong_count() > 0);
+        channels.iter().filter_map(Weak::upgrade).collect()
+    }
+}
+
+impl Drop for StreamWeatherData {
+    fn drop(&mut self) {
+        for channel in self.live_channels() {
+            let mut channel = channel.borrow_mut();
+            channel.closed = true;
+            if let Some(receiver) = channel.receiver.take() {
+                receiver.wake();
+            }
+        }
+    }
+}
+
+pub struct Publish<'a> {
+    subject: &'a StreamWeatherData,
+    measurements: Option<Measurements>,
+}
+
+impl Future for Publish<'_> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let channels = self.subject.live_channels();
+        let mut ready = true;
+        for channel in channels.iter() {
+            let mut channel = channel.borrow_mut();
+            if !channel.has_room() {
+                if !channel
+                    .senders
+                    .iter()
+                    .any(|sender| sender.will_wake(cx.waker()))
+                {
+                    channel.senders.push(cx.waker().clone());
+                }
+                ready = false;
+            }
+        }
+        if !ready {
+            return Poll::Pending;
+        }
+        let measurements = self
+            .measurements
+            .take()
+            .expect("`Publish` polled after completion");
+        for channel in channels {
+            channel.borrow_mut().push(measurements.clone());
+        }
+        Poll::Ready(())
+    }
+}
+
+/// Ends once the `StreamWeatherData` it subscribed to is dropped and drained.
+pub struct MeasurementStream {
+    channel: Rc<RefCell<Channel>>,
+}
+
+impl Stream for MeasurementStream {
+    type Item = Measurements;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Measurements>> {
+        let mut channel = self.channel.borrow_mut();
+        if let Some(measurements) = channel.queue.pop_front() {
+            for sender in channel.senders.drain(..) {
+                sender.wake();
+            }
+            Poll::Ready(Some(measurements))
+        } else if channel.closed {
+            Poll::Ready(None)
+        } else {
+            channel.receiver = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+impl Drop for MeasurementStream {
+    /// Wakes publishers waiting for room in this stream, which no longer holds
+    /// them back.
+    fn drop(&mut self) {
+        for sender in self.channel.borrow_mut().senders.drain(..) {
+            sender.wake();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::measurements::fixture;
+    use futures::executor::{block_on, LocalPool};
+    use futures::task::{noop_waker_ref, waker, ArcWake, LocalSpawnExt};
+    use futures::StreamExt;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn temperatures(stream: MeasurementStream) -> Vec<f64> {
+        block_on(
+            stream
+                .map(|measurements| measurements.temperature())
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn latest_only() {
+        let subject = StreamWeatherData::default();
+        let stream = subject.subscribe(Backpressure::LatestOnly);
+        block_on(async {
+            for temperature in [70.0, 71.0, 72.0] {
+                subject.publish(fixture(temperature, 50.1)).await;
+            }
+        });
+        drop(subject);
+        assert_eq!(temperatures(stream), vec![72.0]);
+    }
+
+    #[test]
+    fn drop_oldest() {
+        let subject = StreamWeatherData::default();
+        let stream = subject.subscribe(Backpressure::DropOldest(2));
+        block_on(async {
+            for temperature in [70.0, 71.0, 72.0] {
+                subject.publish(fixture(temperature, 50.1)).await;
+            }
+        });
+        drop(subject);
+        assert_eq!(temperatures(stream), vec![71.0, 72.0]);
+    }
+
+    #[test]
+    fn bounded_waits_for_reader() {
+        let subject = StreamWeatherData::default();
+        let mut stream = subject.subscribe(Backpressure::Bounded(1));
+        let mut cx = Context::from_waker(noop_waker_ref());
+        let mut first = subject.publish(fixture(70.0, 50.1));
+        assert_eq!(Pin::new(&mut first).poll(&mut cx), Poll::Ready(()));
+        let mut second = subject.publish(fixture(71.0, 50.1));
+        assert_eq!(Pin::new(&mut second).poll(&mut cx), Poll::Pending);
+        assert_eq!(
+            block_on(stream.next()).map(|measurements| measurements.temperature()),
+            Some(70.0)
+        );
+        assert_eq!(Pin::new(&mut second).poll(&mut cx), Poll::Ready(()));
+    }
+
+    #[test]
+    fn bounded_delivers_everything() {
+        let subject = Rc::new(StreamWeatherData::default());
+        let stream = subject.subscribe(Backpressure::Bounded(2));
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let mut pool = LocalPool::new();
+        let spawner = pool.spawner();
+        {
+            let received = Rc::clone(&received);
+            spawner
+                .spawn_local(async move {
+                    let mut stream = stream;
+                    while let Some(measurements) = stream.next().await {
+                        received.borrow_mut().push(measurements.temperature());
+                    }
+                })
+                .unwrap();
+        }
+        spawner
+            .spawn_local(async move {
+                for temperature in 0..10 {
+                    subject.publish(fixture(f64::from(temperature), 50.1)).await;
+                }
+            })
+            .unwrap();
+        pool.run();
+        assert_eq!(
+            *received.borrow(),
+            (0..10).map(f64::from).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn each_subscriber_gets_its_own_stream() {
+        let subject = StreamWeatherData::default();
+        let latest = subject.subscribe(Backpressure::LatestOnly);
+        let queued = subject.subscribe(Backpressure::DropOldest(10));
+        block_on(async {
+            subject.publish(fixture(70.0, 50.1)).await;
+            subject.publish(fixture(71.0, 50.1)).await;
+        });
+        drop(subject);
+        assert_eq!(temperatures(latest), vec![71.0]);
+        assert_eq!(temperatures(queued), vec![70.0, 71.0]);
+    }
+
+    #[test]
+    fn dropped_stream_is_pruned() {
+        let subject = StreamWeatherData::default();
+        let stream = subject.subscribe(Backpressure::Bounded(1));
+        assert_eq!(subject.subscriber_count(), 1);
+        drop(stream);
+        assert_eq!(subject.subscriber_count(), 0);
+        block_on(async {
+            subject.publish(fixture(70.0, 50.1)).await;
+            subject.publish(fixture(71.0, 50.1)).await;
+        });
+    }
+
+    #[derive(Default)]
+    struct WakeCount(AtomicUsize);
+
+    impl ArcWake for WakeCount {
+        fn wake_by_ref(arc_self: &Arc<Self>) {
+            arc_self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn dropping_full_stream_wakes_publisher() {
+        let subject = StreamWeatherData::default();
+        let stream = subject.subscribe(Backpressure::Bounded(1));
+        let wakes = Arc::new(WakeCount::default());
+        let waker = waker(Arc::clone(&wakes));
+        let mut cx = Context::from_waker(&waker);
+        block_on(subject.publish(fixture(70.0, 50.1)));
+        let mut second = subject.publish(fixture(71.0, 50.1));
+        for _ in 0..3 {
+            assert_eq!(Pin::new(&mut second).poll(&mut cx), Poll::Pending);
+        }
+        assert_eq!(stream.channel.borrow().senders.len(), 1);
+        drop(stream);
+        assert_eq!(wakes.0.load(Ordering::SeqCst), 1);
+        assert_eq!(Pin::new(&mut second).poll(&mut cx), Poll::Ready(()));
+    }
+
+    #[test]
+    #[should_panic(expected = "stream capacity must be greater than zero")]
+    fn zero_capacity() {
+        StreamWeatherData::default().subscribe(Backpressure::Bounded(0));
+    }
+}
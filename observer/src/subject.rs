@@ -0,0 +1,900 @@
+use crate::clock::{Clock, SystemClock};
+use crate::filter::Filter;
+use crate::history::{History, Reading};
+use crate::observer::{Observer, PullObserver};
+use crate::publisher::Publisher;
+use crate::schedule::{NotificationMode, Schedule};
+use crate::Measurements;
+use std::cell::{BorrowMutError, RefCell};
+use std::rc::{Rc, Weak};
+use std::time::SystemTime;
+
+pub use crate::publisher::Registrar;
+
+/// Handle returned by `Subject::register_observer`, used to deregister later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObserverId(pub(crate) usize);
+
+#[derive(Debug, PartialEq)]
+pub enum NotifyError {
+    /// Nothing, e.g. no measurements, has been published yet.
+    NothingPublished,
+    /// The listed observers were already borrowed, e.g. by a re-entrant
+    /// notification, and were skipped; every other observer was updated.
+    ObserversBorrowed(Vec<ObserverId>),
+    /// The listed observers panicked during an earlier update and were skipped.
+    ObserversPoisoned(Vec<ObserverId>),
+    UnknownObserver(ObserverId),
+}
+
+/// Read-only view of a subject handed to `PullObserver`s, which fetch only the
+/// fields they need. New fields get a default implementation so that adding
+/// them does not break existing implementors.
+pub trait WeatherView {
+    fn temperature(&self) -> Option<f64>;
+
+    fn humidity(&self) -> Option<f64>;
+
+    fn pressure(&self) -> Option<f64>;
+}
+
+/// Registration and notification for observers of events of type `E`.
+pub trait Subject<E = Measurements> {
+    fn register_observer(&mut self, observer: Rc<RefCell<dyn Observer<E>>>) -> ObserverId;
+
+    /// Registers `observer` without keeping it alive; it is pruned once dropped.
+    fn register_weak_observer(&mut self, observer: Weak<RefCell<dyn Observer<E>>>) -> ObserverId;
+
+    fn register_prioritized_observer(
+        &mut self,
+        observer: Rc<RefCell<dyn Observer<E>>>,
+        priority: i32,
+    ) -> ObserverId {
+        let id = self.register_observer(observer);
+        self.set_priority(id, priority);
+        id
+    }
+
+    /// Observers are notified in descending priority, then in registration
+    /// order. Every observer starts with priority 0. An observer queued through
+    /// a `Registrar` counts as registered when the queue is applied.
+    fn set_priority(&mut self, id: ObserverId, priority: i32) -> bool;
+
+    /// Removes every registration of `observer`, compared by identity.
+    fn remove_observer(&mut self, observer: &Rc<RefCell<dyn Observer<E>>>) -> bool;
+
+    fn unsubscribe(&mut self, id: ObserverId) -> bool;
+
+    /// Notifies observers in priority order. The observers notified are fixed
+    /// when the notification starts: one removed or dropped by another
+    /// observer's `update` is still notified in this cycle, and one added is
+    /// first notified in the next.
+    fn notify_observers(&mut self) -> Result<(), NotifyError>;
+
+    /// Number of registered observers that are still alive.
+    fn observer_count(&self) -> usize;
+}
+
+/// Weather-specific registration on top of `Subject`.
+pub trait WeatherSubject: Subject {
+    fn register_pull_observer(&mut self, observer: Rc<RefCell<dyn PullObserver>>) -> ObserverId;
+
+    fn register_filtered_observer(
+        &mut self,
+        observer: Rc<RefCell<dyn Observer>>,
+        filter: Filter,
+    ) -> ObserverId {
+        let id = self.register_observer(observer);
+        self.set_filter(id, filter);
+        id
+    }
+
+    /// Only notifies the observer registered as `id` of measurements that
+    /// `filter` matches, replacing any previous filter.
+    fn set_filter(&mut self, id: ObserverId, filter: Filter) -> bool;
+}
+
+/// Delivers to a `PullObserver`, giving it the measurements as its view.
+fn pull(
+    observer: Rc<RefCell<dyn PullObserver>>,
+) -> impl Fn(&Measurements) -> Result<(), BorrowMutError> {
+    move |measurements| {
+        observer
+            .try_borrow_mut()
+            .map(|mut observer| observer.update(measurements))
+    }
+}
+
+impl Registrar {
+    pub fn register_pull_observer(&self, observer: Rc<RefCell<dyn PullObserver>>) -> ObserverId {
+        self.register_adapter(pull(observer))
+    }
+}
+
+/// `Publisher<Measurements>` with a reading history, a clock and a
+/// `NotificationMode`.
+pub struct WeatherData {
+    publisher: Publisher<Measurements>,
+    history: History,
+    clock: Box<dyn Clock>,
+    schedule: Schedule,
+}
+
+impl Default for WeatherData {
+    fn default() -> Self {
+        WeatherData {
+            publisher: Publisher::default(),
+            history: History::default(),
+            clock: Box::new(SystemClock),
+            schedule: Schedule::default(),
+        }
+    }
+}
+
+impl WeatherData {
+    /// Keeps the last `capacity` readings so they can be replayed to late subscribers.
+    pub fn with_history(capacity: usize) -> WeatherData {
+        WeatherData {
+            history: History::with_capacity(capacity),
+            ..WeatherData::default()
+        }
+    }
+
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// Replaces the clock used for timestamps and for `NotificationMode` timing.
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) {
+        self.clock = Box::new(clock);
+    }
+
+    pub fn notification_mode(&self) -> NotificationMode {
+        self.schedule.mode()
+    }
+
+    pub fn set_notification_mode(&mut self, mode: NotificationMode) {
+        self.schedule.set_mode(mode);
+    }
+
+    /// Whether a change is being held back by the `NotificationMode`.
+    pub fn has_pending_notification(&self) -> bool {
+        self.schedule.is_pending()
+    }
+
+    /// Delivers a change held back by the `NotificationMode` once it is due.
+    /// Call this periodically when throttling or debouncing.
+    pub fn tick(&mut self) -> Result<(), NotifyError> {
+        if self.schedule.is_due(self.clock.now()) {
+            self.notify_observers()
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Handle for observers that need to register or remove observers from
+    /// inside `update`.
+    pub fn registrar(&self) -> Registrar {
+        self.publisher.registrar()
+    }
+
+    fn measurements_changed(&mut self) -> Result<(), NotifyError> {
+        self.schedule.changed(self.clock.now());
+        self.tick()
+    }
+
+    pub fn set_measurements(&mut self, measurements: Measurements) -> Result<(), NotifyError> {
+        let timestamp = self.clock.now();
+        self.set_measurements_at(measurements, timestamp)
+    }
+
+    pub fn set_measurements_at(
+        &mut self,
+        measurements: Measurements,
+        timestamp: SystemTime,
+    ) -> Result<(), NotifyError> {
+        // A change that became due since the last call is delivered before it is
+        // superseded.
+        let flushed = self.tick();
+        self.history
+            .push(Reading::new(timestamp, measurements.clone()));
+        self.publisher.set_latest(measurements);
+        flushed.and(self.measurements_changed())
+    }
+
+    /// Sends the last `count` readings in the history, oldest first, to the
+    /// observer registered as `id`, bypassing its filter.
+    pub fn replay(&mut self, id: ObserverId, count: usize) -> Result<(), NotifyError> {
+        if !self.publisher.is_registered(id) {
+            return Err(NotifyError::UnknownObserver(id));
+        }
+        for reading in self.history.last(count) {
+            self.publisher.notify_one(id, reading.measurements())?;
+        }
+        Ok(())
+    }
+}
+
+impl WeatherView for WeatherData {
+    fn temperature(&self) -> Option<f64> {
+        self.publisher.latest().map(Measurements::temperature)
+    }
+
+    fn humidity(&self) -> Option<f64> {
+        self.publisher.latest().map(Measurements::humidity)
+    }
+
+    fn pressure(&self) -> Option<f64> {
+        self.publisher.latest().map(Measurements::pressure)
+    }
+}
+
+impl WeatherView for Measurements {
+    fn temperature(&self) -> Option<f64> {
+        Some(Measurements::temperature(self))
+    }
+
+    fn humidity(&self) -> Option<f64> {
+        Some(Measurements::humidity(self))
+    }
+
+    fn pressure(&self) -> Option<f64> {
+        Some(Measurements::pressure(self))
+    }
+}
+
+impl Subject for WeatherData {
+    fn register_observer(&mut self, observer: Rc<RefCell<dyn Observer>>) -> ObserverId {
+        self.publisher.register_observer(observer)
+    }
+
+    fn register_weak_observer(&mut self, observer: Weak<RefCell<dyn Observer>>) -> ObserverId {
+        self.publisher.register_weak_observer(observer)
+    }
+
+    fn set_priority(&mut self, id: ObserverId, priority: i32) -> bool {
+        self.publisher.set_priority(id, priority)
+    }
+
+    fn remove_observer(&mut self, observer: &Rc<RefCell<dyn Observer>>) -> bool {
+        self.publisher.remove_observer(observer)
+    }
+
+    fn unsubscribe(&mut self, id: ObserverId) -> bool {
+        self.publisher.unsubscribe(id)
+    }
+
+    fn notify_observers(&mut self) -> Result<(), NotifyError> {
+        if self.publisher.latest().is_none() {
+            return Err(NotifyError::NothingPublished);
+        }
+        self.schedule.notified(self.clock.now());
+        self.publisher.notify_observers()
+    }
+
+    fn observer_count(&self) -> usize {
+        self.publisher.observer_count()
+    }
+}
+
+impl WeatherSubject for WeatherData {
+    fn register_pull_observer(&mut self, observer: Rc<RefCell<dyn PullObserver>>) -> ObserverId {
+        self.publisher.register_adapter(pull(observer))
+    }
+
+    fn set_filter(&mut self, id: ObserverId, mut filter: Filter) -> bool {
+        self.publisher
+            .set_filter(id, move |measurements| filter.matches(measurements))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::filter::{Fields, Filter};
+    use crate::log::{JsonFormatter, LogFormatter, Retention, TextFormatter};
+    use crate::measurements::fixture;
+    use crate::observer::*;
+    use crate::schedule::NotificationMode;
+    use crate::Measurements;
+    use std::cell::RefCell;
+    use std::rc::{Rc, Weak};
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn update_observer() {
+        let mut weather_data = WeatherData::default();
+        let current_display_a = Rc::new(RefCell::new(CurrentConditionsDisplay::default()));
+        let current_display_b = Rc::clone(&current_display_a);
+        weather_data.register_observer(current_display_a);
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        assert_eq!(
+            current_display_b.borrow().get_logs().format(&TextFormatter),
+            vec!["Current conditions: 72.3F degrees and 50.1% humidity"]
+        );
+    }
+
+    #[test]
+    fn remove_observer() {
+        let mut weather_data = WeatherData::default();
+        let current_display_a = Rc::new(RefCell::new(CurrentConditionsDisplay::default()));
+        let current_display_b = Rc::clone(&current_display_a);
+        let observer: Rc<RefCell<dyn Observer>> = current_display_a;
+        weather_data.register_observer(Rc::clone(&observer));
+        assert!(weather_data.remove_observer(&observer));
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        assert!(current_display_b.borrow().get_logs().is_empty());
+    }
+
+    #[test]
+    fn remove_observer_unregistered() {
+        let mut weather_data = WeatherData::default();
+        let observer: Rc<RefCell<dyn Observer>> =
+            Rc::new(RefCell::new(CurrentConditionsDisplay::default()));
+        assert!(!weather_data.remove_observer(&observer));
+    }
+
+    #[test]
+    fn remove_observer_keeps_others() {
+        let mut weather_data = WeatherData::default();
+        let current_display_a = Rc::new(RefCell::new(CurrentConditionsDisplay::default()));
+        let current_display_b = Rc::new(RefCell::new(CurrentConditionsDisplay::default()));
+        let observer: Rc<RefCell<dyn Observer>> = current_display_a.clone();
+        weather_data.register_observer(Rc::clone(&observer));
+        weather_data.register_observer(current_display_b.clone());
+        weather_data.remove_observer(&observer);
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        assert!(current_display_a.borrow().get_logs().is_empty());
+        assert_eq!(current_display_b.borrow().get_logs().len(), 1);
+    }
+
+    #[test]
+    fn unsubscribe() {
+        let mut weather_data = WeatherData::default();
+        let current_display = Rc::new(RefCell::new(CurrentConditionsDisplay::default()));
+        let id = weather_data.register_observer(current_display.clone());
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        assert!(weather_data.unsubscribe(id));
+        assert!(!weather_data.unsubscribe(id));
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        assert_eq!(current_display.borrow().get_logs().len(), 1);
+    }
+
+    #[test]
+    fn register_observer_returns_distinct_ids() {
+        let mut weather_data = WeatherData::default();
+        let current_display = Rc::new(RefCell::new(CurrentConditionsDisplay::default()));
+        let id_a = weather_data.register_observer(current_display.clone());
+        let id_b = weather_data.register_observer(current_display);
+        assert_ne!(id_a, id_b);
+    }
+
+    #[test]
+    fn register_weak_observer() {
+        let mut weather_data = WeatherData::default();
+        let current_display = Rc::new(RefCell::new(CurrentConditionsDisplay::default()));
+        let observer: Weak<RefCell<dyn Observer>> = Rc::downgrade(&current_display) as _;
+        weather_data.register_weak_observer(observer);
+        assert_eq!(Rc::strong_count(&current_display), 1);
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        assert_eq!(current_display.borrow().get_logs().len(), 1);
+    }
+
+    #[test]
+    fn weak_observer_is_pruned_when_dropped() {
+        let mut weather_data = WeatherData::default();
+        let current_display_a = Rc::new(RefCell::new(CurrentConditionsDisplay::default()));
+        let current_display_b = Rc::new(RefCell::new(CurrentConditionsDisplay::default()));
+        let observer: Weak<RefCell<dyn Observer>> = Rc::downgrade(&current_display_a) as _;
+        weather_data.register_weak_observer(observer);
+        weather_data.register_observer(current_display_b.clone());
+        assert_eq!(weather_data.observer_count(), 2);
+        drop(current_display_a);
+        assert_eq!(weather_data.observer_count(), 1);
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        assert_eq!(current_display_b.borrow().get_logs().len(), 1);
+        assert_eq!(weather_data.observer_count(), 1);
+    }
+
+    #[test]
+    fn remove_weak_observer() {
+        let mut weather_data = WeatherData::default();
+        let current_display = Rc::new(RefCell::new(CurrentConditionsDisplay::default()));
+        let observer: Rc<RefCell<dyn Observer>> = current_display.clone();
+        weather_data.register_weak_observer(Rc::downgrade(&observer));
+        assert!(weather_data.remove_observer(&observer));
+        assert_eq!(weather_data.observer_count(), 0);
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        assert!(current_display.borrow().get_logs().is_empty());
+    }
+
+    #[test]
+    fn notify_observers_before_measurements() {
+        let mut weather_data = WeatherData::default();
+        assert_eq!(
+            weather_data.notify_observers(),
+            Err(NotifyError::NothingPublished)
+        );
+    }
+
+    #[test]
+    fn notify_observers_skips_borrowed_observer() {
+        let mut weather_data = WeatherData::default();
+        let current_display_a = Rc::new(RefCell::new(CurrentConditionsDisplay::default()));
+        let current_display_b = Rc::new(RefCell::new(CurrentConditionsDisplay::default()));
+        let id = weather_data.register_observer(current_display_a.clone());
+        weather_data.register_observer(current_display_b.clone());
+        {
+            let _borrow = current_display_a.borrow();
+            assert_eq!(
+                weather_data.set_measurements(fixture(72.3, 50.1)),
+                Err(NotifyError::ObserversBorrowed(vec![id]))
+            );
+        }
+        assert!(current_display_a.borrow().get_logs().is_empty());
+        assert_eq!(current_display_b.borrow().get_logs().len(), 1);
+        weather_data.notify_observers().unwrap();
+        assert_eq!(current_display_a.borrow().get_logs().len(), 1);
+    }
+
+    #[test]
+    fn update_every_display() {
+        let mut weather_data = WeatherData::default();
+        let statistics_display = Rc::new(RefCell::new(StatisticsDisplay::default()));
+        let forecast_display = Rc::new(RefCell::new(ForecastDisplay::default()));
+        weather_data.register_observer(statistics_display.clone());
+        weather_data.register_observer(forecast_display.clone());
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        assert_eq!(statistics_display.borrow().temperature().count(), 1);
+        assert_eq!(forecast_display.borrow().forecast(), Some(Forecast::Same));
+    }
+
+    #[test]
+    fn update_pull_observer() {
+        let mut weather_data = WeatherData::default();
+        let current_display = Rc::new(RefCell::new(CurrentConditionsDisplay::default()));
+        let id = weather_data.register_pull_observer(current_display.clone());
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        assert_eq!(
+            current_display.borrow().get_logs().format(&TextFormatter),
+            vec!["Current conditions: 72.3F degrees and 50.1% humidity"]
+        );
+        assert!(weather_data.unsubscribe(id));
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        assert_eq!(current_display.borrow().get_logs().len(), 1);
+    }
+
+    #[test]
+    fn pull_observer_fetches_only_what_it_needs() {
+        #[derive(Default)]
+        struct PressureLog(Vec<Option<f64>>);
+
+        impl PullObserver for PressureLog {
+            fn update(&mut self, subject: &dyn WeatherView) {
+                self.0.push(subject.pressure());
+            }
+        }
+
+        let mut weather_data = WeatherData::default();
+        let pressure_log = Rc::new(RefCell::new(PressureLog::default()));
+        weather_data.register_pull_observer(pressure_log.clone());
+        weather_data.register_observer(Rc::new(RefCell::new(StatisticsDisplay::default())));
+        assert_eq!(weather_data.observer_count(), 2);
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        assert_eq!(pressure_log.borrow().0, vec![Some(29.92)]);
+    }
+
+    #[test]
+    fn weather_view() {
+        let mut weather_data = WeatherData::default();
+        assert_eq!(weather_data.temperature(), None);
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        assert_eq!(weather_data.temperature(), Some(72.3));
+        assert_eq!(weather_data.humidity(), Some(50.1));
+        assert_eq!(weather_data.pressure(), Some(29.92));
+    }
+
+    #[test]
+    fn filtered_observer() {
+        let mut weather_data = WeatherData::default();
+        let current_display_a = Rc::new(RefCell::new(CurrentConditionsDisplay::default()));
+        let current_display_b = Rc::new(RefCell::new(CurrentConditionsDisplay::default()));
+        weather_data.register_filtered_observer(
+            current_display_a.clone(),
+            Filter::changed_by(Fields::TEMPERATURE, 2.0),
+        );
+        weather_data.register_observer(current_display_b.clone());
+        for temperature in [72.0, 73.0, 75.0, 74.5] {
+            let measurements = fixture(temperature, 50.0);
+            weather_data.set_measurements(measurements).unwrap();
+        }
+        assert_eq!(
+            current_display_a.borrow().get_logs().format(&TextFormatter),
+            vec![
+                "Current conditions: 72F degrees and 50% humidity",
+                "Current conditions: 75F degrees and 50% humidity",
+            ]
+        );
+        assert_eq!(current_display_b.borrow().get_logs().len(), 4);
+    }
+
+    #[test]
+    fn set_filter() {
+        let mut weather_data = WeatherData::default();
+        let current_display = Rc::new(RefCell::new(CurrentConditionsDisplay::default()));
+        let id = weather_data.register_pull_observer(current_display.clone());
+        assert!(weather_data.set_filter(id, Filter::changed(Fields::HUMIDITY)));
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        assert_eq!(current_display.borrow().get_logs().len(), 1);
+        weather_data.unsubscribe(id);
+        assert!(!weather_data.set_filter(id, Filter::new(|_| true)));
+    }
+
+    #[test]
+    fn history() {
+        let mut weather_data = WeatherData::with_history(2);
+        for seconds in 1..=3 {
+            let timestamp = SystemTime::UNIX_EPOCH + Duration::from_secs(seconds);
+            weather_data
+                .set_measurements_at(fixture(72.3, 50.1), timestamp)
+                .unwrap();
+        }
+        let timestamps: Vec<_> = weather_data
+            .history()
+            .iter()
+            .map(|reading| reading.timestamp())
+            .collect();
+        assert_eq!(
+            timestamps,
+            vec![
+                SystemTime::UNIX_EPOCH + Duration::from_secs(2),
+                SystemTime::UNIX_EPOCH + Duration::from_secs(3),
+            ]
+        );
+    }
+
+    #[test]
+    fn replay_to_late_subscriber() {
+        let mut weather_data = WeatherData::with_history(10);
+        for temperature in [70.0, 71.0, 72.0] {
+            let measurements = fixture(temperature, 50.0);
+            weather_data.set_measurements(measurements).unwrap();
+        }
+        let current_display = Rc::new(RefCell::new(CurrentConditionsDisplay::default()));
+        let id = weather_data.register_observer(current_display.clone());
+        weather_data.replay(id, 2).unwrap();
+        assert_eq!(
+            current_display.borrow().get_logs().format(&TextFormatter),
+            vec![
+                "Current conditions: 71F degrees and 50% humidity",
+                "Current conditions: 72F degrees and 50% humidity",
+            ]
+        );
+    }
+
+    #[test]
+    fn replay_to_pull_observer() {
+        let mut weather_data = WeatherData::with_history(10);
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        let current_display = Rc::new(RefCell::new(CurrentConditionsDisplay::default()));
+        let id = weather_data.register_pull_observer(current_display.clone());
+        weather_data.replay(id, 5).unwrap();
+        assert_eq!(current_display.borrow().get_logs().len(), 1);
+    }
+
+    #[test]
+    fn replay_unknown_observer() {
+        let mut weather_data = WeatherData::with_history(10);
+        let current_display = Rc::new(RefCell::new(CurrentConditionsDisplay::default()));
+        let id = weather_data.register_observer(current_display);
+        weather_data.unsubscribe(id);
+        assert_eq!(
+            weather_data.replay(id, 1),
+            Err(NotifyError::UnknownObserver(id))
+        );
+    }
+
+    fn temperatures(display: &Rc<RefCell<StatisticsDisplay>>) -> u32 {
+        display.borrow().temperature().count()
+    }
+
+    #[test]
+    fn throttle() {
+        let clock = ManualClock::default();
+        let mut weather_data = WeatherData::default();
+        weather_data.set_clock(clock.clone());
+        weather_data.set_notification_mode(NotificationMode::Throttle(Duration::from_secs(1)));
+        let statistics_display = Rc::new(RefCell::new(StatisticsDisplay::default()));
+        weather_data.register_observer(statistics_display.clone());
+        for _ in 0..100 {
+            weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+            clock.advance(Duration::from_millis(5));
+        }
+        assert_eq!(temperatures(&statistics_display), 1);
+        assert!(weather_data.has_pending_notification());
+        clock.advance(Duration::from_millis(500));
+        weather_data.tick().unwrap();
+        assert_eq!(temperatures(&statistics_display), 2);
+        assert!(!weather_data.has_pending_notification());
+        weather_data.tick().unwrap();
+        assert_eq!(temperatures(&statistics_display), 2);
+    }
+
+    #[test]
+    fn debounce() {
+        let clock = ManualClock::default();
+        let mut weather_data = WeatherData::default();
+        weather_data.set_clock(clock.clone());
+        weather_data.set_notification_mode(NotificationMode::Debounce(Duration::from_millis(100)));
+        let current_display = Rc::new(RefCell::new(CurrentConditionsDisplay::default()));
+        weather_data.register_observer(current_display.clone());
+        for temperature in [70.0, 71.0, 72.0] {
+            let measurements = fixture(temperature, 50.0);
+            weather_data.set_measurements(measurements).unwrap();
+            clock.advance(Duration::from_millis(60));
+            weather_data.tick().unwrap();
+        }
+        assert!(current_display.borrow().get_logs().is_empty());
+        clock.advance(Duration::from_millis(40));
+        weather_data.tick().unwrap();
+        assert_eq!(
+            current_display.borrow().get_logs().format(&TextFormatter),
+            vec!["Current conditions: 72F degrees and 50% humidity"]
+        );
+    }
+
+    #[test]
+    fn debounce_flushes_due_change_before_superseding_it() {
+        let clock = ManualClock::default();
+        let mut weather_data = WeatherData::default();
+        weather_data.set_clock(clock.clone());
+        weather_data.set_notification_mode(NotificationMode::Debounce(Duration::from_millis(100)));
+        let statistics_display = Rc::new(RefCell::new(StatisticsDisplay::default()));
+        weather_data.register_observer(statistics_display.clone());
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        clock.advance(Duration::from_millis(200));
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        assert_eq!(temperatures(&statistics_display), 1);
+        assert!(weather_data.has_pending_notification());
+    }
+
+    #[test]
+    fn clock_timestamps_history() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH + Duration::from_secs(60));
+        let mut weather_data = WeatherData::with_history(1);
+        weather_data.set_clock(clock);
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        assert_eq!(
+            weather_data.history().iter().next().unwrap().timestamp(),
+            SystemTime::UNIX_EPOCH + Duration::from_secs(60)
+        );
+    }
+
+    #[test]
+    fn structured_logs() {
+        let mut weather_data = WeatherData::default();
+        let current_display = Rc::new(RefCell::new(CurrentConditionsDisplay::default()));
+        current_display
+            .borrow_mut()
+            .get_logs_mut()
+            .set_display("porch");
+        current_display
+            .borrow_mut()
+            .get_logs_mut()
+            .set_retention(Retention::Last(2));
+        current_display
+            .borrow_mut()
+            .get_logs_mut()
+            .set_clock(ManualClock::new(
+                SystemTime::UNIX_EPOCH + Duration::from_millis(1500),
+            ));
+        weather_data.register_observer(current_display.clone());
+        for _ in 0..3 {
+            weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        }
+        let display = current_display.borrow();
+        let logs = display.get_logs();
+        assert_eq!(logs.len(), 2);
+        let entry = logs.last().unwrap();
+        assert_eq!(entry.display(), "porch");
+        assert_eq!(entry.number("temperature"), Some(72.3));
+        assert_eq!(entry.number("humidity"), Some(50.1));
+        assert_eq!(
+            JsonFormatter.format(entry),
+            r#"{"timestamp":1.5,"display":"porch","summary":"Current conditions: 72.3F degrees and 50.1% humidity","fields":{"temperature":72.3,"humidity":50.1}}"#
+        );
+    }
+
+    struct Recorder {
+        name: &'static str,
+        calls: Rc<RefCell<Vec<&'static str>>>,
+        dropped_on_update: Option<Rc<RefCell<Recorder>>>,
+    }
+
+    impl Recorder {
+        fn new(
+            name: &'static str,
+            calls: &Rc<RefCell<Vec<&'static str>>>,
+        ) -> Rc<RefCell<Recorder>> {
+            Rc::new(RefCell::new(Recorder {
+                name,
+                calls: Rc::clone(calls),
+                dropped_on_update: None,
+            }))
+        }
+    }
+
+    impl Observer for Recorder {
+        fn update(&mut self, _metrics: &Measurements) {
+            self.calls.borrow_mut().push(self.name);
+            self.dropped_on_update = None;
+        }
+    }
+
+    #[test]
+    fn priority_order() {
+        let calls = Rc::default();
+        let mut weather_data = WeatherData::default();
+        weather_data.register_observer(Recorder::new("display a", &calls));
+        weather_data.register_prioritized_observer(Recorder::new("persistence", &calls), 100);
+        weather_data.register_prioritized_observer(Recorder::new("background", &calls), -10);
+        weather_data.register_observer(Recorder::new("display b", &calls));
+        weather_data.register_prioritized_observer(Recorder::new("alerts", &calls), 10);
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        assert_eq!(
+            *calls.borrow(),
+            vec![
+                "persistence",
+                "alerts",
+                "display a",
+                "display b",
+                "background"
+            ]
+        );
+    }
+
+    #[test]
+    fn set_priority_keeps_registration_order_among_equals() {
+        let calls = Rc::default();
+        let mut weather_data = WeatherData::default();
+        let id_a = weather_data.register_observer(Recorder::new("a", &calls));
+        let id_b = weather_data.register_observer(Recorder::new("b", &calls));
+        let id_c = weather_data.register_observer(Recorder::new("c", &calls));
+        assert!(weather_data.set_priority(id_c, 5));
+        assert!(weather_data.set_priority(id_a, 5));
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        assert_eq!(*calls.borrow(), vec!["a", "c", "b"]);
+        weather_data.unsubscribe(id_b);
+        assert!(!weather_data.set_priority(id_b, 1));
+    }
+
+    #[test]
+    fn observer_dropped_during_notification_is_still_notified() {
+        let calls = Rc::default();
+        let mut weather_data = WeatherData::default();
+        let dropped = Recorder::new("dropped", &calls);
+        let dropper = Recorder::new("dropper", &calls);
+        let observer: Weak<RefCell<dyn Observer>> = Rc::downgrade(&dropped) as _;
+        dropper.borrow_mut().dropped_on_update = Some(dropped);
+        weather_data.register_prioritized_observer(dropper, 1);
+        weather_data.register_weak_observer(observer);
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        assert_eq!(*calls.borrow(), vec!["dropper", "dropped"]);
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        assert_eq!(*calls.borrow(), vec!["dropper", "dropped", "dropper"]);
+        assert_eq!(weather_data.observer_count(), 1);
+    }
+
+    struct SelfRemover {
+        registrar: Registrar,
+        id: Option<ObserverId>,
+        updates: u32,
+    }
+
+    impl Observer for SelfRemover {
+        fn update(&mut self, _metrics: &Measurements) {
+            self.updates += 1;
+            if let Some(id) = self.id.take() {
+                self.registrar.unsubscribe(id);
+            }
+        }
+    }
+
+    #[test]
+    fn observer_unsubscribes_itself() {
+        let mut weather_data = WeatherData::default();
+        let remover = Rc::new(RefCell::new(SelfRemover {
+            registrar: weather_data.registrar(),
+            id: None,
+            updates: 0,
+        }));
+        let current_display = Rc::new(RefCell::new(CurrentConditionsDisplay::default()));
+        let id = weather_data.register_observer(remover.clone());
+        weather_data.register_observer(current_display.clone());
+        remover.borrow_mut().id = Some(id);
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        assert_eq!(weather_data.observer_count(), 1);
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        assert_eq!(remover.borrow().updates, 1);
+        assert_eq!(current_display.borrow().get_logs().len(), 2);
+    }
+
+    #[test]
+    fn observer_registers_another() {
+        struct Registering {
+            registrar: Registrar,
+            display: Option<Rc<RefCell<CurrentConditionsDisplay>>>,
+        }
+
+        impl Observer for Registering {
+            fn update(&mut self, _metrics: &Measurements) {
+                if let Some(display) = self.display.take() {
+                    self.registrar.register_observer(display);
+                }
+            }
+        }
+
+        let mut weather_data = WeatherData::default();
+        let current_display = Rc::new(RefCell::new(CurrentConditionsDisplay::default()));
+        weather_data.register_observer(Rc::new(RefCell::new(Registering {
+            registrar: weather_data.registrar(),
+            display: Some(current_display.clone()),
+        })));
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        assert_eq!(weather_data.observer_count(), 2);
+        assert!(current_display.borrow().get_logs().is_empty());
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        assert_eq!(current_display.borrow().get_logs().len(), 1);
+    }
+
+    #[test]
+    fn registrar_ids_do_not_collide() {
+        let mut weather_data = WeatherData::default();
+        let registrar = weather_data.registrar();
+        let current_display = Rc::new(RefCell::new(CurrentConditionsDisplay::default()));
+        let queued = registrar.register_observer(current_display.clone());
+        let registered = weather_data.register_observer(current_display.clone());
+        assert_ne!(queued, registered);
+        assert_eq!(weather_data.observer_count(), 2);
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        registrar.unsubscribe(queued);
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        assert_eq!(weather_data.observer_count(), 1);
+        assert_eq!(current_display.borrow().get_logs().len(), 3);
+    }
+
+    #[test]
+    fn queued_registration_applies_before_the_next_call() {
+        let calls = Rc::default();
+        let mut weather_data = WeatherData::default();
+        weather_data
+            .registrar()
+            .register_observer(Recorder::new("queued", &calls));
+        let direct = weather_data.register_observer(Recorder::new("direct", &calls));
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        assert!(weather_data.set_priority(direct, 0));
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        assert_eq!(
+            *calls.borrow(),
+            vec!["queued", "direct", "queued", "direct"]
+        );
+    }
+
+    #[test]
+    fn queued_changes_apply_outside_notification() {
+        let mut weather_data = WeatherData::with_history(10);
+        weather_data.set_measurements(fixture(72.3, 50.1)).unwrap();
+        let registrar = weather_data.registrar();
+        let current_display = Rc::new(RefCell::new(CurrentConditionsDisplay::default()));
+        let id = registrar.register_observer(current_display.clone());
+        assert_eq!(weather_data.observer_count(), 1);
+        assert!(weather_data.set_priority(id, 1));
+        weather_data.replay(id, 1).unwrap();
+        assert_eq!(current_display.borrow().get_logs().len(), 1);
+        registrar.unsubscribe(id);
+        assert_eq!(weather_data.observer_count(), 0);
+        assert!(!weather_data.unsubscribe(id));
+    }
+}